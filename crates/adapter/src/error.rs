@@ -0,0 +1,50 @@
+use kernel::error::KernelError;
+use rusqlite::ErrorCode;
+
+/// Classifies a SQLite failure for callers that don't know about SQLite.
+pub(crate) fn sql_error(err: rusqlite::Error) -> KernelError {
+    match &err {
+        rusqlite::Error::SqliteFailure(failure, _) => match failure.code {
+            ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => {
+                KernelError::Corrupt(err.to_string())
+            }
+            ErrorCode::DatabaseBusy
+            | ErrorCode::DatabaseLocked
+            | ErrorCode::ConstraintViolation => KernelError::Conflict(err.to_string()),
+            ErrorCode::ReadOnly => KernelError::ReadOnly,
+            _ => KernelError::Storage(Box::new(err)),
+        },
+        // A row that doesn't fit the model was written by something else.
+        rusqlite::Error::FromSqlConversionFailure(..)
+        | rusqlite::Error::InvalidColumnType(..)
+        | rusqlite::Error::IntegralValueOutOfRange(..) => KernelError::Corrupt(err.to_string()),
+        _ => KernelError::Storage(Box::new(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::*;
+
+    #[test]
+    fn it_reports_bad_rows_as_corrupt() {
+        let conn = Connection::open_in_memory().unwrap();
+        let err = conn
+            .query_row("select 'not a number'", [], |row| row.get::<_, i64>(0))
+            .unwrap_err();
+
+        assert!(matches!(sql_error(err), KernelError::Corrupt(_)));
+    }
+
+    #[test]
+    fn it_reports_constraint_violations_as_conflicts() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("create table t (id text primary key); insert into t values ('a');")
+            .unwrap();
+        let err = conn.execute("insert into t values ('a')", []).unwrap_err();
+
+        assert!(matches!(sql_error(err), KernelError::Conflict(_)));
+    }
+}
@@ -1 +1,2 @@
+mod error;
 pub mod repository_impl;
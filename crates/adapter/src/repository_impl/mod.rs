@@ -1 +1,2 @@
 pub mod note_repository_impl;
+pub mod transaction_impl;
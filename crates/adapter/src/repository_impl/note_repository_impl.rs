@@ -1,129 +1,296 @@
-use std::error::Error;
+use std::rc::Rc;
 
+use chrono::{DateTime, Utc};
+use clock::{Clock, SystemClock};
 use db::utils::gen_id;
 use derive_new::new;
 use kernel::{
-    model::note::{Note, UpdateNoteActiveEvent, UpdateNoteBodyEvent, UpdateNoteBoundsEvent},
-    repository::note_repository::NoteRepository,
+    error::{KernelError, Result},
+    model::{
+        note::{
+            Note, NoteSearchResult, NoteSortKey, SNIPPET_MATCH_END, SNIPPET_MATCH_START,
+            UpdateNoteActiveEvent, UpdateNoteBodyEvent, UpdateNoteBoundsEvent,
+        },
+        revision::NoteRevision,
+    },
+    repository::{note_repository::NoteRepository, transaction::Transaction},
 };
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension, Params, Row};
+
+use super::transaction_impl::TransactionImpl;
+use crate::error::sql_error;
+
+const SEARCH_LIMIT: usize = 50;
+const SNIPPET_TOKENS: usize = 12;
+
+/// Columns read by `note_from_row`, in order.
+const NOTE_COLUMNS: &str = "
+              notes.id
+            , notes.body
+            , notes.width
+            , notes.height
+            , notes.location_x
+            , notes.location_y
+            , notes.is_active
+            , notes.created_at
+            , notes.updated_at
+            , notes.closed_at
+            , notes.deleted_at
+";
+
+/// Sorts notes by the line `Note::title` shows: the body without its leading
+/// blank lines and spaces, or "Untitled" when there is nothing else.
+const TITLE_KEY: &str = "
+            CASE ltrim(notes.body, char(9, 10, 13, 32))
+                WHEN '' THEN 'untitled'
+                ELSE lower(ltrim(notes.body, char(9, 10, 13, 32)))
+            END
+";
+
+/// Columns read by `revision_from_row`, in order.
+const REVISION_COLUMNS: &str = "
+              id
+            , note_id
+            , body
+            , created_at
+";
 
 #[derive(new)]
 pub struct NoteRepositoryImpl {
     pub connection: Connection,
+    #[new(value = "Rc::new(SystemClock)")]
+    clock: Rc<dyn Clock>,
+}
+
+impl NoteRepositoryImpl {
+    /// Uses `clock` instead of the system time for every timestamp written.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Rc::new(clock);
+        self
+    }
+
+    fn now_millis(&self) -> i64 {
+        self.clock.now_utc().timestamp_millis()
+    }
+
+    fn execute(&self, sql: &str, params: impl Params) -> Result<usize> {
+        self.connection.execute(sql, params).map_err(sql_error)
+    }
+
+    fn query_all<T>(
+        &self,
+        sql: &str,
+        params: impl Params,
+        from_row: impl FnMut(&Row) -> rusqlite::Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut stmt = self.connection.prepare(sql).map_err(sql_error)?;
+        let rows = stmt
+            .query_map(params, from_row)
+            .map_err(sql_error)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sql_error)?;
+        Ok(rows)
+    }
+
+    fn query_optional<T>(
+        &self,
+        sql: &str,
+        params: impl Params,
+        from_row: impl FnOnce(&Row) -> rusqlite::Result<T>,
+    ) -> Result<Option<T>> {
+        self.connection
+            .query_row(sql, params, from_row)
+            .optional()
+            .map_err(sql_error)
+    }
+
+    /// Fails with `NotFound` unless a statement touched a note.
+    fn expect_note(changed: usize, id: &str) -> Result<()> {
+        if changed == 0 {
+            return Err(KernelError::note_not_found(id));
+        }
+        Ok(())
+    }
 }
 
 impl NoteRepository for NoteRepositoryImpl {
-    fn get_notes(&self) -> Result<Vec<Note>, Box<dyn Error>> {
-        let mut stmt = self.connection.prepare(
-            "
-            SELECT
-              id
-            , body
-            , width
-            , height
-            , location_x
-            , location_y
-            , is_active
-            FROM notes
-            ORDER BY id DESC;
-            ",
-        )?;
+    fn transaction(&self) -> Result<Box<dyn Transaction + '_>> {
+        Ok(Box::new(TransactionImpl::begin(&self.connection)?))
+    }
 
-        let notes = stmt
-            .query_map([], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    body: row.get(1)?,
-                    width: row.get(2)?,
-                    height: row.get(3)?,
-                    location_x: row.get(4)?,
-                    location_y: row.get(5)?,
-                    is_active: row.get(6)?,
-                })
-            })?
-            .map(|note| note.unwrap())
-            .collect();
+    fn get_notes(&self, sort: NoteSortKey) -> Result<Vec<Note>> {
+        let order_by = match sort {
+            NoteSortKey::Created => "notes.created_at DESC, notes.id DESC",
+            NoteSortKey::Updated => "notes.updated_at DESC, notes.id DESC",
+            NoteSortKey::Title => &format!("{TITLE_KEY}, notes.id"),
+        };
+        let notes = self.query_all(
+            &format!(
+                "
+                SELECT {NOTE_COLUMNS}
+                FROM notes
+                WHERE deleted_at IS NULL
+                ORDER BY {order_by};
+                "
+            ),
+            [],
+            note_from_row,
+        )?;
         Ok(notes)
     }
 
-    fn get_note_by_id(&self, id: &str) -> Result<Option<Note>, Box<dyn Error>> {
-        let mut stmt = self.connection.prepare(
-            "
-            SELECT
-              id
-            , body
-            , width
-            , height
-            , location_x
-            , location_y
-            , is_active
-            FROM notes
-            WHERE id = ?1
-            ",
-        )?;
+    fn get_note_by_id(&self, id: &str) -> Result<Option<Note>> {
+        self.query_optional(
+            &format!(
+                "
+                SELECT {NOTE_COLUMNS}
+                FROM notes
+                WHERE id = ?1
+                "
+            ),
+            [id],
+            note_from_row,
+        )
+    }
+
+    fn search_notes(&self, query: &str) -> Result<Vec<NoteSearchResult>> {
+        let Some(query) = to_match_query(query) else {
+            return Ok(vec![]);
+        };
 
-        let note = stmt
-            .query_map([id], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    body: row.get(1)?,
-                    width: row.get(2)?,
-                    height: row.get(3)?,
-                    location_x: row.get(4)?,
-                    location_y: row.get(5)?,
-                    is_active: row.get(6)?,
+        self.query_all(
+            &format!(
+                "
+                SELECT {NOTE_COLUMNS}
+                , snippet(notes_fts, 1, ?2, ?3, '…', ?4)
+                , bm25(notes_fts) AS rank
+                FROM notes_fts
+                JOIN notes ON notes.id = notes_fts.id
+                WHERE notes_fts MATCH ?1
+                AND notes.deleted_at IS NULL
+                ORDER BY rank
+                LIMIT ?5
+                "
+            ),
+            (
+                &query,
+                SNIPPET_MATCH_START.to_string(),
+                SNIPPET_MATCH_END.to_string(),
+                SNIPPET_TOKENS,
+                SEARCH_LIMIT,
+            ),
+            |row| {
+                Ok(NoteSearchResult {
+                    note: note_from_row(row)?,
+                    snippet: row.get(11)?,
+                    rank: row.get(12)?,
                 })
-            })?
-            .find(|note| note.is_ok())
-            .unwrap();
+            },
+        )
+    }
 
-        if let Ok(note) = note {
-            Ok(Some(note))
-        } else {
-            Ok(None)
-        }
+    fn create_note(&self) -> Result<Note> {
+        let note = Note::new(gen_id(), self.clock.now_utc());
+        let now = note.created_at.timestamp_millis();
+        self.connection
+            .query_row(
+                &format!(
+                    "
+                    insert into notes (
+                      id
+                    , body
+                    , is_active
+                    , width
+                    , height
+                    , location_x
+                    , location_y
+                    , created_at
+                    , updated_at
+                    ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    returning {NOTE_COLUMNS}
+                    "
+                ),
+                (
+                    &note.id,
+                    &note.body,
+                    note.is_active,
+                    note.width,
+                    note.height,
+                    note.location_x,
+                    note.location_y,
+                    now,
+                    now,
+                ),
+                note_from_row,
+            )
+            .map_err(sql_error)
     }
 
-    fn create_note(&self) -> Result<Note, Box<dyn Error>> {
-        let id = gen_id();
-        let _ = self.connection.execute(
+    fn save_note(&self, note: &Note) -> Result<()> {
+        self.execute(
             "
             insert into notes (
               id
             , body
-            , is_active
             , width
             , height
             , location_x
             , location_y
-            ) values ($1, $2, $3, $4, $5, $6, $7)
+            , is_active
+            , created_at
+            , updated_at
+            , closed_at
+            , deleted_at
+            ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            on conflict (id) do update set
+              body = excluded.body
+            , width = excluded.width
+            , height = excluded.height
+            , location_x = excluded.location_x
+            , location_y = excluded.location_y
+            , is_active = excluded.is_active
+            , created_at = excluded.created_at
+            , updated_at = excluded.updated_at
+            , closed_at = excluded.closed_at
+            , deleted_at = excluded.deleted_at
             ",
-            (&id, "", true, 200, 200, 200, 200),
+            (
+                &note.id,
+                &note.body,
+                note.width,
+                note.height,
+                note.location_x,
+                note.location_y,
+                note.is_active,
+                note.created_at.timestamp_millis(),
+                note.updated_at.timestamp_millis(),
+                note.closed_at.map(|at| at.timestamp_millis()),
+                note.deleted_at.map(|at| at.timestamp_millis()),
+            ),
         )?;
-        let note = self.get_note_by_id(&id)?.unwrap();
-        Ok(note)
+        Ok(())
     }
 
-    fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Result<String, Box<dyn Error>> {
-        self.connection.execute(
+    fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Result<String> {
+        let changed = self.execute(
             "
-            update notes set body = ?1 where id = ?2
+            update notes set body = ?1, updated_at = ?2 where id = ?3 and deleted_at IS NULL
             ",
-            (&event.body, &event.id),
+            (&event.body, self.now_millis(), &event.id),
         )?;
+        Self::expect_note(changed, &event.id)?;
         Ok(event.id)
     }
 
-    fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Result<String, Box<dyn Error>> {
-        self.connection.execute(
+    fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Result<String> {
+        let changed = self.execute(
             "
             update notes set
               width = ?1
             , height = ?2
             , location_x = ?3
             , location_y = ?4
-            where id = ?5
+            where id = ?5 and deleted_at IS NULL
             ",
             (
                 &f32::from(event.bounds.size.width),
@@ -133,26 +300,542 @@ impl NoteRepository for NoteRepositoryImpl {
                 &event.id,
             ),
         )?;
+        Self::expect_note(changed, &event.id)?;
         Ok(event.id)
     }
 
-    fn update_note_active(&self, event: UpdateNoteActiveEvent) -> Result<(), Box<dyn Error>> {
-        self.connection.execute(
+    fn update_note_active(&self, event: UpdateNoteActiveEvent) -> Result<()> {
+        let closed_at = (!event.is_active).then(|| self.now_millis());
+        let changed = self.execute(
             "
-            update notes set is_active = ?1 where id = ?2
+            update notes set is_active = ?1, closed_at = ?2 where id = ?3
             ",
-            (&f32::from(event.is_active), &event.id),
+            (event.is_active, closed_at, &event.id),
         )?;
-        Ok(())
+        Self::expect_note(changed, &event.id)
     }
 
-    fn delete_note_by_id(&self, id: &str) -> Result<(), Box<dyn Error>> {
-        self.connection.execute(
+    fn delete_note_by_id(&self, id: &str) -> Result<()> {
+        let changed = self.execute(
             "
-            delete from notes where id = ?1
+            update notes set deleted_at = ?1 where id = ?2
+            ",
+            (self.now_millis(), id),
+        )?;
+        Self::expect_note(changed, id)
+    }
+
+    fn get_trashed_notes(&self) -> Result<Vec<Note>> {
+        self.query_all(
+            &format!(
+                "
+                SELECT {NOTE_COLUMNS}
+                FROM notes
+                WHERE deleted_at IS NOT NULL
+                ORDER BY deleted_at DESC;
+                "
+            ),
+            [],
+            note_from_row,
+        )
+    }
+
+    fn restore_note_by_id(&self, id: &str) -> Result<()> {
+        let changed = self.execute(
+            "
+            update notes set deleted_at = NULL where id = ?1 and deleted_at IS NOT NULL
             ",
             (id,),
         )?;
-        Ok(())
+        Self::expect_note(changed, id)
+    }
+
+    fn purge_note_by_id(&self, id: &str) -> Result<()> {
+        let changed = self.execute(
+            "
+            delete from notes where id = ?1 and deleted_at IS NOT NULL
+            ",
+            (id,),
+        )?;
+        Self::expect_note(changed, id)
+    }
+
+    fn purge_trashed_notes(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        self.execute(
+            "
+            delete from notes where deleted_at < ?1
+            ",
+            (deleted_before.timestamp_millis(),),
+        )
+    }
+
+    fn create_revision(&self, note_id: &str) -> Result<NoteRevision> {
+        self.query_optional(
+            &format!(
+                "
+                insert into note_revisions (id, note_id, body, created_at)
+                select ?1, id, body, ?2 from notes where id = ?3
+                returning {REVISION_COLUMNS}
+                "
+            ),
+            (gen_id(), self.now_millis(), note_id),
+            revision_from_row,
+        )?
+        .ok_or_else(|| KernelError::note_not_found(note_id))
+    }
+
+    fn get_revisions(&self, note_id: &str) -> Result<Vec<NoteRevision>> {
+        self.query_all(
+            &format!(
+                "
+                SELECT {REVISION_COLUMNS}
+                FROM note_revisions
+                WHERE note_id = ?1
+                ORDER BY created_at DESC, id DESC;
+                "
+            ),
+            [note_id],
+            revision_from_row,
+        )
+    }
+
+    fn get_revision_by_id(&self, id: &str) -> Result<Option<NoteRevision>> {
+        self.query_optional(
+            &format!(
+                "
+                SELECT {REVISION_COLUMNS}
+                FROM note_revisions
+                WHERE id = ?1
+                "
+            ),
+            [id],
+            revision_from_row,
+        )
+    }
+
+    fn get_latest_revision(&self, note_id: &str) -> Result<Option<NoteRevision>> {
+        self.query_optional(
+            &format!(
+                "
+                SELECT {REVISION_COLUMNS}
+                FROM note_revisions
+                WHERE note_id = ?1
+                ORDER BY created_at DESC, id DESC
+                LIMIT 1
+                "
+            ),
+            [note_id],
+            revision_from_row,
+        )
+    }
+
+    fn data_version(&self) -> Result<i64> {
+        self.connection
+            .query_row("PRAGMA data_version", [], |row| row.get(0))
+            .map_err(sql_error)
+    }
+
+    fn schema_version(&self) -> Result<u32> {
+        self.connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(sql_error)
+    }
+}
+
+fn note_from_row(row: &Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        body: row.get(1)?,
+        width: row.get(2)?,
+        height: row.get(3)?,
+        location_x: row.get(4)?,
+        location_y: row.get(5)?,
+        is_active: row.get(6)?,
+        created_at: from_millis(7, row.get(7)?)?,
+        updated_at: from_millis(8, row.get(8)?)?,
+        closed_at: row
+            .get::<_, Option<i64>>(9)?
+            .map(|millis| from_millis(9, millis))
+            .transpose()?,
+        deleted_at: row
+            .get::<_, Option<i64>>(10)?
+            .map(|millis| from_millis(10, millis))
+            .transpose()?,
+    })
+}
+
+fn revision_from_row(row: &Row) -> rusqlite::Result<NoteRevision> {
+    Ok(NoteRevision {
+        id: row.get(0)?,
+        note_id: row.get(1)?,
+        body: row.get(2)?,
+        created_at: from_millis(3, row.get(3)?)?,
+    })
+}
+
+fn from_millis(idx: usize, millis: i64) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or(rusqlite::Error::IntegralValueOutOfRange(idx, millis))
+}
+
+/// Turns free text into an FTS5 query: every word is quoted so punctuation
+/// is never parsed as query syntax, and matched as a prefix so results show
+/// up while the user is still typing.
+fn to_match_query(query: &str) -> Option<String> {
+    let terms = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clock::ManualClock;
+    use db::{Db, utils::TempDir};
+
+    use super::*;
+
+    fn repository() -> NoteRepositoryImpl {
+        NoteRepositoryImpl::new(Db::open_in_memory().unwrap().conn)
+    }
+
+    fn create_note_with_body(repository: &NoteRepositoryImpl, body: &str) -> String {
+        let note = repository.create_note().unwrap();
+        repository
+            .update_note_body(UpdateNoteBodyEvent {
+                id: note.id,
+                body: body.to_string(),
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn it_builds_match_queries() {
+        assert_eq!(to_match_query("  "), None);
+        assert_eq!(
+            to_match_query("buy \"milk"),
+            Some("\"buy\"* \"\"\"milk\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn it_searches_note_bodies() {
+        let repository = repository();
+        let id = create_note_with_body(&repository, "buy milk and eggs");
+        create_note_with_body(&repository, "call the dentist");
+
+        let results = repository.search_notes("mil").unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note.id, id);
+        assert_eq!(
+            results[0].snippet,
+            format!("buy {SNIPPET_MATCH_START}milk{SNIPPET_MATCH_END} and eggs")
+        );
+    }
+
+    #[test]
+    fn it_sorts_notes_by_title() {
+        let repository = repository();
+        let banana = create_note_with_body(&repository, "banana");
+        let apple = create_note_with_body(&repository, "  Apple");
+        let cherry = create_note_with_body(&repository, "\n\n\t cherry\nstones");
+        let untitled = create_note_with_body(&repository, " \n");
+
+        let notes = repository.get_notes(NoteSortKey::Title).unwrap();
+
+        let ids = notes.into_iter().map(|note| note.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![apple, banana, cherry, untitled]);
+    }
+
+    #[test]
+    fn it_stamps_notes_with_the_clock() {
+        let start = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let clock = ManualClock::at(start);
+        let repository = repository().with_clock(clock.clone());
+
+        let id = repository.create_note().unwrap().id;
+        clock.advance(Duration::from_secs(5));
+        repository
+            .update_note_body(UpdateNoteBodyEvent {
+                id: id.clone(),
+                body: "edited".to_string(),
+            })
+            .unwrap();
+
+        let note = repository.get_note_by_id(&id).unwrap().unwrap();
+        assert_eq!(note.created_at, start);
+        assert_eq!(note.updated_at, start + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn it_records_when_a_note_was_closed() {
+        let repository = repository();
+        let id = repository.create_note().unwrap().id;
+
+        repository
+            .update_note_active(UpdateNoteActiveEvent {
+                id: id.clone(),
+                is_active: false,
+            })
+            .unwrap();
+        let closed = repository.get_note_by_id(&id).unwrap().unwrap();
+        repository
+            .update_note_active(UpdateNoteActiveEvent {
+                id: id.clone(),
+                is_active: true,
+            })
+            .unwrap();
+        let reopened = repository.get_note_by_id(&id).unwrap().unwrap();
+
+        assert!(closed.closed_at.is_some());
+        assert!(reopened.closed_at.is_none());
+    }
+
+    #[test]
+    fn it_returns_none_for_an_unknown_id() {
+        let repository = repository();
+
+        assert!(repository.get_note_by_id("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn it_fails_to_update_an_unknown_note() {
+        let repository = repository();
+
+        let result = repository.update_note_body(UpdateNoteBodyEvent {
+            id: "missing".to_string(),
+            body: "hello".to_string(),
+        });
+
+        assert!(matches!(result, Err(KernelError::NotFound { .. })));
+    }
+
+    #[test]
+    fn it_reports_a_corrupt_row_instead_of_panicking() {
+        let repository = repository();
+        create_note_with_body(&repository, "fine");
+        repository
+            .connection
+            .execute("update notes set created_at = ?1", [i64::MAX])
+            .unwrap();
+
+        let result = repository.get_notes(NoteSortKey::default());
+
+        assert!(matches!(result, Err(KernelError::Corrupt(_))));
+    }
+
+    #[test]
+    fn it_snapshots_revisions() {
+        let repository = repository();
+        let id = create_note_with_body(&repository, "first");
+        let first = repository.create_revision(&id).unwrap();
+        create_note_with_body(&repository, "other");
+        repository
+            .update_note_body(UpdateNoteBodyEvent {
+                id: id.clone(),
+                body: "second".to_string(),
+            })
+            .unwrap();
+        let second = repository.create_revision(&id).unwrap();
+
+        let revisions = repository.get_revisions(&id).unwrap();
+
+        assert_eq!(first.body, "first");
+        assert_eq!(second.body, "second");
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(
+            repository
+                .get_latest_revision(&id)
+                .unwrap()
+                .unwrap()
+                .note_id,
+            id
+        );
+        assert!(repository.create_revision("missing").is_err());
+    }
+
+    #[test]
+    fn it_keeps_the_index_in_sync() {
+        let repository = repository();
+        let id = create_note_with_body(&repository, "old text");
+        repository
+            .update_note_body(UpdateNoteBodyEvent {
+                id: id.clone(),
+                body: "new text".to_string(),
+            })
+            .unwrap();
+
+        assert!(repository.search_notes("old").unwrap().is_empty());
+        assert_eq!(repository.search_notes("new").unwrap().len(), 1);
+
+        repository.delete_note_by_id(&id).unwrap();
+
+        assert!(repository.search_notes("new").unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_saves_notes_as_they_are() {
+        let repository = repository();
+        let created_at = DateTime::from_timestamp_millis(1_000).unwrap();
+        let mut note = Note {
+            body: "imported".to_string(),
+            width: 320.,
+            is_active: false,
+            closed_at: Some(created_at),
+            ..Note::new("imported-id".to_string(), created_at)
+        };
+
+        repository.save_note(&note).unwrap();
+        note.body = "replaced".to_string();
+        repository.save_note(&note).unwrap();
+
+        let saved = repository.get_note_by_id("imported-id").unwrap().unwrap();
+        assert_eq!(saved.body, "replaced");
+        assert_eq!(saved.width, 320.);
+        assert!(!saved.is_active);
+        assert_eq!(saved.created_at, created_at);
+        assert_eq!(saved.closed_at, Some(created_at));
+        assert_eq!(repository.search_notes("replaced").unwrap().len(), 1);
+        assert!(repository.search_notes("imported").unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_moves_deleted_notes_to_the_trash() {
+        let repository = repository();
+        let id = repository.create_note().unwrap().id;
+
+        repository.delete_note_by_id(&id).unwrap();
+
+        assert!(
+            repository
+                .get_notes(NoteSortKey::default())
+                .unwrap()
+                .is_empty()
+        );
+        let trashed = repository.get_trashed_notes().unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert!(trashed[0].deleted_at.is_some());
+
+        repository.restore_note_by_id(&id).unwrap();
+
+        assert!(repository.get_trashed_notes().unwrap().is_empty());
+        assert_eq!(
+            repository.get_notes(NoteSortKey::default()).unwrap().len(),
+            1
+        );
+        assert!(matches!(
+            repository.restore_note_by_id(&id),
+            Err(KernelError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn it_does_not_edit_trashed_notes() {
+        let repository = repository();
+        let id = repository.create_note().unwrap().id;
+        repository.delete_note_by_id(&id).unwrap();
+
+        assert!(matches!(
+            repository.update_note_body(UpdateNoteBodyEvent {
+                id: id.clone(),
+                body: "milk".to_string(),
+            }),
+            Err(KernelError::NotFound { .. })
+        ));
+        assert!(matches!(
+            repository.update_note_bounds(UpdateNoteBoundsEvent {
+                id: id.clone(),
+                bounds: Default::default(),
+            }),
+            Err(KernelError::NotFound { .. })
+        ));
+        assert_eq!(repository.get_note_by_id(&id).unwrap().unwrap().body, "");
+    }
+
+    #[test]
+    fn it_purges_notes_trashed_before_the_cutoff() {
+        let repository = repository();
+        let trashed = repository.create_note().unwrap().id;
+        let kept = repository.create_note().unwrap().id;
+        repository.delete_note_by_id(&trashed).unwrap();
+
+        let purged = repository
+            .purge_trashed_notes(Utc::now() + chrono::Duration::seconds(1))
+            .unwrap();
+
+        assert_eq!(purged, 1);
+        assert!(repository.get_note_by_id(&trashed).unwrap().is_none());
+        assert!(repository.get_note_by_id(&kept).unwrap().is_some());
+    }
+
+    #[test]
+    fn it_rolls_back_an_uncommitted_transaction() {
+        let repository = repository();
+        let kept = repository.create_note().unwrap().id;
+
+        {
+            let _transaction = repository.transaction().unwrap();
+            repository.create_note().unwrap();
+            repository.delete_note_by_id(&kept).unwrap();
+        }
+
+        let notes = repository.get_notes(NoteSortKey::default()).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, kept);
+    }
+
+    #[test]
+    fn it_commits_nested_transactions_with_the_outer_one() {
+        let repository = repository();
+
+        let outer = repository.transaction().unwrap();
+        let inner = repository.transaction().unwrap();
+        let id = repository.create_note().unwrap().id;
+        inner.commit().unwrap();
+        let rolled_back = repository.transaction().unwrap();
+        repository.create_note().unwrap();
+        drop(rolled_back);
+        outer.commit().unwrap();
+
+        let notes = repository.get_notes(NoteSortKey::default()).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, id);
+    }
+
+    #[test]
+    fn it_fails_to_snapshot_an_unknown_note() {
+        let repository = repository();
+
+        let result = repository.create_revision("missing");
+
+        assert!(matches!(result, Err(KernelError::NotFound { .. })));
+    }
+
+    #[test]
+    fn it_changes_the_data_version_on_commits_elsewhere() {
+        let dir = TempDir::new();
+        let path = dir.join("database.sqlite");
+        let mut db = Db::open(&path).unwrap();
+        db.prepare_database().unwrap();
+        let repository = NoteRepositoryImpl::new(db.conn);
+        let elsewhere = NoteRepositoryImpl::new(Db::open(&path).unwrap().conn);
+
+        let before = repository.data_version().unwrap();
+        repository.create_note().unwrap();
+        let after_own = repository.data_version().unwrap();
+        elsewhere.create_note().unwrap();
+        let after_other = repository.data_version().unwrap();
+
+        assert_eq!(after_own, before);
+        assert_ne!(after_other, before);
     }
 }
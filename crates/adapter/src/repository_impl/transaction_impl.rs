@@ -0,0 +1,45 @@
+use kernel::{error::Result, repository::transaction::Transaction};
+use rusqlite::Connection;
+
+use crate::error::sql_error;
+
+/// A transaction backed by a savepoint, so that it nests inside any
+/// transaction that is already open on the connection.
+pub struct TransactionImpl<'a> {
+    connection: &'a Connection,
+    committed: bool,
+}
+
+impl<'a> TransactionImpl<'a> {
+    pub fn begin(connection: &'a Connection) -> Result<Self> {
+        connection
+            .execute_batch("SAVEPOINT unit_of_work")
+            .map_err(sql_error)?;
+        Ok(Self {
+            connection,
+            committed: false,
+        })
+    }
+}
+
+impl Transaction for TransactionImpl<'_> {
+    fn commit(mut self: Box<Self>) -> Result<()> {
+        self.connection
+            .execute_batch("RELEASE unit_of_work")
+            .map_err(sql_error)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TransactionImpl<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            // Nothing can be done about a failed rollback here; SQLite rolls
+            // the whole transaction back on its own when it cannot keep it.
+            let _ = self
+                .connection
+                .execute_batch("ROLLBACK TO unit_of_work; RELEASE unit_of_work");
+        }
+    }
+}
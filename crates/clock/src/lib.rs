@@ -0,0 +1,105 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time, so that anything timing- or
+/// timestamp-dependent can be tested without sleeping.
+pub trait Clock {
+    /// Monotonic time, for measuring delays.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps that get stored.
+    fn now_utc(&self) -> DateTime<Utc>;
+}
+
+/// The real clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to. Both kinds of time move together,
+/// and clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<(Instant, DateTime<Utc>)>>,
+}
+
+impl ManualClock {
+    /// A clock stopped at the current time.
+    pub fn new() -> Self {
+        Self::at(Utc::now())
+    }
+
+    /// A clock stopped at the wall-clock time `now`.
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Rc::new(Cell::new((Instant::now(), now))),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let (instant, utc) = self.now.get();
+        let utc = chrono::Duration::from_std(by)
+            .ok()
+            .and_then(|by| utc.checked_add_signed(by))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        self.now.set((instant + by, utc));
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get().0
+    }
+
+    fn now_utc(&self) -> DateTime<Utc> {
+        self.now.get().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_shares_time_between_clones() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+
+        clock.clone().advance(Duration::from_secs(1));
+
+        assert_eq!(clock.now() - start, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn it_advances_wall_time() {
+        let start = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let clock = ManualClock::at(start);
+
+        clock.advance(Duration::from_millis(1500));
+
+        assert_eq!(
+            clock.now_utc().timestamp_millis(),
+            start.timestamp_millis() + 1500
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! The control socket of a running sticky instance. Clients write one JSON
+//! [`Request`] per line and read one JSON [`Response`] per line back, e.g.
+//!
+//! ```text
+//! {"command":"create","body":"buy milk"}
+//! {"status":"ok","notes":[{"id":"01J…","body":"buy milk","is_active":true}]}
+//! ```
+
+#[cfg(unix)]
+pub mod client;
+#[cfg(unix)]
+pub mod server;
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Creates a note with `body` and opens it.
+    Create {
+        body: String,
+    },
+    /// Opens a note, showing it again if it was hidden.
+    Show {
+        id: String,
+    },
+    /// Hides a note, closing its window.
+    Hide {
+        id: String,
+    },
+    ShowAll,
+    HideAll,
+    /// Lists every note that is not in the trash.
+    List,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Response {
+    /// The notes the request created, showed, hid or listed.
+    Ok {
+        notes: Vec<NoteInfo>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl Response {
+    pub fn error(message: impl ToString) -> Self {
+        Self::Error {
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoteInfo {
+    pub id: String,
+    pub body: String,
+    pub is_active: bool,
+}
+
+/// The socket of the instance that has `db_path` open, so that instances
+/// using different databases do not answer for each other.
+pub fn socket_path(db_path: &Path) -> PathBuf {
+    db_path.with_extension("sock")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_requests_as_tagged_json() {
+        let request: Request =
+            serde_json::from_str(r#"{"command":"create","body":"buy milk"}"#).unwrap();
+        assert_eq!(
+            request,
+            Request::Create {
+                body: "buy milk".to_string()
+            }
+        );
+
+        let request: Request = serde_json::from_str(r#"{"command":"hide_all"}"#).unwrap();
+        assert_eq!(request, Request::HideAll);
+    }
+
+    #[test]
+    fn it_writes_responses_as_tagged_json() {
+        let response = Response::error("note x does not exist");
+
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"status":"error","message":"note x does not exist"}"#
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::PermissionsExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    thread,
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    executor::block_on,
+};
+
+use crate::{Request, Response};
+
+/// Owner read and write: other users cannot drive this instance.
+const SOCKET_MODE: u32 = 0o600;
+
+/// A request together with the channel its response goes back through.
+pub type Incoming = (Request, oneshot::Sender<Response>);
+
+/// Listens on `socket` from a thread of its own and hands every request
+/// out through the returned receiver, to be answered wherever the state of
+/// the app lives. A socket left behind by an instance that crashed is
+/// replaced; one that is still answered fails with `AddrInUse`.
+pub fn listen(socket: &Path) -> io::Result<mpsc::UnboundedReceiver<Incoming>> {
+    let listener = bind(socket)?;
+    let (sender, requests) = mpsc::unbounded();
+    thread::Builder::new()
+        .name("sticky-control".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || serve(stream, &sender));
+            }
+        })?;
+    Ok(requests)
+}
+
+/// Binds `socket` so that only the user running sticky can connect to it.
+fn bind(socket: &Path) -> io::Result<UnixListener> {
+    let listener = match UnixListener::bind(socket) {
+        Err(err) if err.kind() == io::ErrorKind::AddrInUse => {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(err);
+            }
+            fs::remove_file(socket)?;
+            UnixListener::bind(socket)
+        }
+        result => result,
+    }?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(SOCKET_MODE))?;
+    Ok(listener)
+}
+
+/// Answers the requests of one client, one line at a time, until it hangs
+/// up.
+fn serve(stream: UnixStream, sender: &mpsc::UnboundedSender<Incoming>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => dispatch(sender, request),
+            Err(err) => Response::error(format!("invalid request: {err}")),
+        };
+        let mut line = serde_json::to_string(&response)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn dispatch(sender: &mpsc::UnboundedSender<Incoming>, request: Request) -> Response {
+    let (reply, response) = oneshot::channel();
+    if sender.unbounded_send((request, reply)).is_err() {
+        return Response::error("sticky is shutting down");
+    }
+    block_on(response).unwrap_or_else(|_| Response::error("sticky is shutting down"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::{NoteInfo, client};
+
+    fn socket(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sticky-{}-{name}.sock", std::process::id()))
+    }
+
+    /// Answers every request with a note whose body names the command.
+    fn answer(mut requests: mpsc::UnboundedReceiver<Incoming>) {
+        thread::spawn(move || {
+            while let Some((request, reply)) = block_on(requests.next()) {
+                let note = NoteInfo {
+                    id: "a".to_string(),
+                    body: format!("{request:?}"),
+                    is_active: true,
+                };
+                reply.send(Response::Ok { notes: vec![note] }).ok();
+            }
+        });
+    }
+
+    #[test]
+    fn it_answers_requests_over_the_socket() {
+        let path = socket("answers");
+        answer(listen(&path).unwrap());
+
+        let response = client::send(&path, &Request::ShowAll).unwrap();
+
+        let Response::Ok { notes } = response else {
+            panic!("unexpected response {response:?}");
+        };
+        assert_eq!(notes[0].body, "ShowAll");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_keeps_the_socket_to_its_owner() {
+        let path = socket("owner");
+        let _requests = listen(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+
+        assert_eq!(mode & 0o777, SOCKET_MODE);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_invalid_requests() {
+        let path = socket("invalid");
+        answer(listen(&path).unwrap());
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(b"{\"command\":\"explode\"}\n").unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn it_replaces_a_socket_left_behind() {
+        let path = socket("stale");
+        drop(UnixListener::bind(&path).unwrap());
+
+        answer(listen(&path).unwrap());
+
+        assert!(client::send(&path, &Request::List).is_ok());
+        assert_eq!(
+            listen(&path).err().map(|err| err.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,11 @@
+-- `IF NOT EXISTS` keeps databases created before migrations existed
+-- (user_version 0 with a notes table) working.
+CREATE TABLE IF NOT EXISTS notes (
+  id TEXT NOT NULL
+, body TEXT NOT NULL
+, width INTEGER NOT NULL
+, height INTEGER NOT NULL
+, location_x INTEGER NOT NULL
+, location_y INTEGER NOT NULL
+, is_active BOOLEAN NOT NULL
+);
@@ -0,0 +1,21 @@
+-- The index keeps its own copy of the body keyed by note id rather than
+-- pointing at `notes.rowid`, which is not stable across VACUUM for tables
+-- without an INTEGER PRIMARY KEY.
+CREATE VIRTUAL TABLE notes_fts USING fts5(
+  id UNINDEXED
+, body
+);
+
+INSERT INTO notes_fts (id, body) SELECT id, body FROM notes;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+  INSERT INTO notes_fts (id, body) VALUES (new.id, new.body);
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF id, body ON notes BEGIN
+  UPDATE notes_fts SET id = new.id, body = new.body WHERE id = old.id;
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+  DELETE FROM notes_fts WHERE id = old.id;
+END;
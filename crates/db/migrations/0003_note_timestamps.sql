@@ -0,0 +1,21 @@
+-- Timestamps are unix epoch milliseconds.
+ALTER TABLE notes ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE notes ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE notes ADD COLUMN closed_at INTEGER;
+
+-- Note ids are ULIDs, whose first ten characters are the creation time in
+-- milliseconds encoded as Crockford base32, so existing notes can recover
+-- their real creation time.
+UPDATE notes SET created_at =
+    (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 1, 1)) - 1) * 35184372088832
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 2, 1)) - 1) * 1099511627776
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 3, 1)) - 1) * 34359738368
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 4, 1)) - 1) * 1073741824
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 5, 1)) - 1) * 33554432
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 6, 1)) - 1) * 1048576
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 7, 1)) - 1) * 32768
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 8, 1)) - 1) * 1024
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 9, 1)) - 1) * 32
+  + (instr('0123456789ABCDEFGHJKMNPQRSTVWXYZ', substr(id, 10, 1)) - 1);
+
+UPDATE notes SET updated_at = created_at;
@@ -0,0 +1,2 @@
+-- Unix epoch milliseconds at which the note was moved to the trash.
+ALTER TABLE notes ADD COLUMN deleted_at INTEGER;
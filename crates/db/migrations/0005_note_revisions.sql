@@ -0,0 +1,14 @@
+-- Snapshots of note bodies, taken at most once per interval while typing
+-- and whenever a note is closed.
+CREATE TABLE note_revisions (
+  id TEXT NOT NULL PRIMARY KEY
+, note_id TEXT NOT NULL
+, body TEXT NOT NULL
+, created_at INTEGER NOT NULL
+);
+
+CREATE INDEX note_revisions_note_id ON note_revisions (note_id, created_at);
+
+CREATE TRIGGER note_revisions_purge AFTER DELETE ON notes BEGIN
+  DELETE FROM note_revisions WHERE note_id = old.id;
+END;
@@ -0,0 +1,75 @@
+-- Rebuilds `notes` as a STRICT table keyed by id. Geometry was declared
+-- INTEGER but always written as floats, and `is_active` was written as
+-- 0.0/1.0, so both are normalised while the rows are copied over.
+CREATE TABLE notes_strict (
+  id TEXT NOT NULL PRIMARY KEY
+, body TEXT NOT NULL
+, width REAL NOT NULL
+, height REAL NOT NULL
+, location_x REAL NOT NULL
+, location_y REAL NOT NULL
+, is_active INTEGER NOT NULL CHECK (is_active IN (0, 1))
+, created_at INTEGER NOT NULL
+, updated_at INTEGER NOT NULL
+, closed_at INTEGER
+, deleted_at INTEGER
+) STRICT;
+
+-- Ids are ULIDs, so duplicates are not expected; should an old file have
+-- them anyway, the most recently updated copy is kept.
+INSERT INTO notes_strict
+SELECT
+  id
+, body
+, CAST(width AS REAL)
+, CAST(height AS REAL)
+, CAST(location_x AS REAL)
+, CAST(location_y AS REAL)
+, CASE WHEN is_active THEN 1 ELSE 0 END
+, CAST(created_at AS INTEGER)
+, CAST(updated_at AS INTEGER)
+, CAST(closed_at AS INTEGER)
+, CAST(deleted_at AS INTEGER)
+FROM notes
+WHERE true
+ORDER BY updated_at
+ON CONFLICT (id) DO UPDATE SET
+  body = excluded.body
+, width = excluded.width
+, height = excluded.height
+, location_x = excluded.location_x
+, location_y = excluded.location_y
+, is_active = excluded.is_active
+, created_at = excluded.created_at
+, updated_at = excluded.updated_at
+, closed_at = excluded.closed_at
+, deleted_at = excluded.deleted_at;
+
+-- Dropping the table also drops its triggers; they are recreated below.
+DROP TABLE notes;
+ALTER TABLE notes_strict RENAME TO notes;
+
+-- Reindex from the surviving rows, in case duplicates were folded.
+DELETE FROM notes_fts;
+INSERT INTO notes_fts (id, body) SELECT id, body FROM notes;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+  INSERT INTO notes_fts (id, body) VALUES (new.id, new.body);
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF id, body ON notes BEGIN
+  UPDATE notes_fts SET id = new.id, body = new.body WHERE id = old.id;
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+  DELETE FROM notes_fts WHERE id = old.id;
+END;
+
+CREATE TRIGGER note_revisions_purge AFTER DELETE ON notes BEGIN
+  DELETE FROM note_revisions WHERE note_id = old.id;
+END;
+
+-- The note list and the trash, newest first, and the trash purge.
+CREATE INDEX notes_created_at ON notes (created_at) WHERE deleted_at IS NULL;
+CREATE INDEX notes_updated_at ON notes (updated_at) WHERE deleted_at IS NULL;
+CREATE INDEX notes_deleted_at ON notes (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -1,37 +1,94 @@
+pub mod error;
+pub mod migration;
+pub mod path;
+pub mod recovery;
+pub mod snapshot;
 pub mod utils;
 
-use rusqlite::{Connection, Result};
+use std::{fs, path::Path};
+
+use rusqlite::{Connection, OpenFlags};
+
+use error::{DbError, Result};
 
 pub struct Db {
     pub conn: Connection,
 }
 
 impl Db {
-    pub fn new() -> Result<Self> {
-        let conn = Self::connect()?;
+    /// Opens the database in WAL mode, so that reading it never waits for
+    /// a write in progress.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Self::connect(path)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
         Ok(Self { conn })
     }
 
-    pub fn connect() -> Result<Connection> {
-        let conn = Connection::open("./database.sqlite")?;
+    pub fn connect(path: &Path) -> Result<Connection> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
         Ok(conn)
     }
 
-    pub fn prepare_database(&self) -> Result<()> {
-        self.conn.execute(
-            "
-            CREATE TABLE IF NOT EXISTS notes (
-              id TEXT NOT NULL
-            , body TEXT NOT NULL
-            , width INTEGER NOT NULL
-            , height INTEGER NOT NULL
-            , location_x INTEGER NOT NULL
-            , location_y INTEGER NOT NULL
-            , is_active BOOLEAN NOT NULL
-            );
-            ",
-            (),
+    /// Opens a migrated database that only lives as long as the connection,
+    /// for tests and for trying the app out without touching any notes.
+    pub fn open_in_memory() -> Result<Self> {
+        let mut db = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        db.prepare_database()?;
+        Ok(db)
+    }
+
+    /// Opens an existing database without ever writing to it. It has to be
+    /// at the current schema version already, since it cannot be migrated.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
+        let db = Self { conn };
+        db.quick_check()?;
+        recovery::ensure_current(&db)?;
+        Ok(db)
+    }
+
+    pub fn prepare_database(&mut self) -> Result<()> {
+        migration::migrate(&mut self.conn)
+    }
+
+    /// Fails with [`DbError::Corrupt`] if SQLite finds damage in the file.
+    pub fn quick_check(&self) -> Result<()> {
+        let result: String = self
+            .conn
+            .query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if result != "ok" {
+            return Err(DbError::Corrupt(result));
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn it_opens_files_in_wal_mode() {
+        let dir = TempDir::new();
+        let db = Db::open(&dir.join("database.sqlite")).unwrap();
+
+        let mode: String = db
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(mode, "wal");
+    }
+}
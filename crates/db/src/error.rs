@@ -0,0 +1,57 @@
+use std::io;
+
+use rusqlite::ErrorCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DbError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(
+        "database schema version {found} is newer than the version {supported} supported by this build"
+    )]
+    SchemaTooNew { found: u32, supported: u32 },
+    #[error(
+        "database schema version {found} is older than the version {required} this build needs, and a read-only database cannot be upgraded"
+    )]
+    SchemaTooOld { found: u32, required: u32 },
+    #[error("the database failed its integrity check: {0}")]
+    Corrupt(String),
+}
+
+/// What kind of failure an error is, as far as recovering from it goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Another process holds a lock on the database.
+    Busy,
+    /// The file is damaged or not a database at all.
+    Corrupt,
+    /// The database can be read, but not written.
+    ReadOnly,
+    Other,
+}
+
+impl DbError {
+    pub fn failure(&self) -> Failure {
+        match self {
+            DbError::Sqlite(rusqlite::Error::SqliteFailure(failure, _)) => match failure.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Failure::Busy,
+                ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => Failure::Corrupt,
+                ErrorCode::ReadOnly | ErrorCode::PermissionDenied => Failure::ReadOnly,
+                _ => Failure::Other,
+            },
+            DbError::Io(err) => match err.kind() {
+                io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+                    Failure::ReadOnly
+                }
+                _ => Failure::Other,
+            },
+            DbError::Corrupt(_) => Failure::Corrupt,
+            _ => Failure::Other,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, DbError>;
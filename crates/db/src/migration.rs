@@ -0,0 +1,245 @@
+use rusqlite::Connection;
+
+use crate::error::{DbError, Result};
+
+/// A single schema change. `version` is written to `PRAGMA user_version`
+/// once `sql` has been applied, so migrations must never be edited after
+/// they have shipped; add a new one instead.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create notes table",
+        sql: include_str!("../migrations/0001_create_notes.sql"),
+    },
+    Migration {
+        version: 2,
+        description: "full-text index over note bodies",
+        sql: include_str!("../migrations/0002_notes_fts.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "created, updated and closed timestamps",
+        sql: include_str!("../migrations/0003_note_timestamps.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "soft delete",
+        sql: include_str!("../migrations/0004_note_trash.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "note revisions",
+        sql: include_str!("../migrations/0005_note_revisions.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "strict notes table with a primary key and indexes",
+        sql: include_str!("../migrations/0006_strict_notes.sql"),
+    },
+];
+
+/// The schema version this build writes.
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    Ok(version)
+}
+
+/// Applies every pending migration inside a single transaction.
+///
+/// Refuses to touch a database whose version is newer than this build knows
+/// about, since an older binary would otherwise write rows a newer schema
+/// does not expect.
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    run(conn, MIGRATIONS)
+}
+
+fn run(conn: &mut Connection, migrations: &[Migration]) -> Result<()> {
+    let current = schema_version(conn)?;
+    let supported = migrations.last().map_or(0, |migration| migration.version);
+    if current > supported {
+        return Err(DbError::SchemaTooNew {
+            found: current,
+            supported,
+        });
+    }
+
+    let tx = conn.transaction()?;
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT count(*) FROM sqlite_master WHERE name = ?1",
+            [name],
+            |row| row.get::<_, i64>(0),
+        )
+        .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn it_migrates_a_fresh_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert!(table_exists(&conn, "notes"));
+    }
+
+    #[test]
+    fn it_is_idempotent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        migrate(&mut conn).unwrap();
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn it_keeps_notes_of_an_unversioned_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        conn.execute(
+            "insert into notes values ('a', 'hello', 200, 200, 200, 200, 1)",
+            (),
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let body: String = conn
+            .query_row("select body from notes where id = 'a'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(body, "hello");
+    }
+
+    #[test]
+    fn it_backfills_creation_time_from_ulids() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+        let id = ulid::Ulid::new();
+        conn.execute(
+            "insert into notes values (?1, '', 200, 200, 200, 200, 1)",
+            [id.to_string()],
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let (created_at, updated_at): (u64, u64) = conn
+            .query_row("select created_at, updated_at from notes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(created_at, id.timestamp_ms());
+        assert_eq!(updated_at, created_at);
+    }
+
+    #[test]
+    fn it_normalises_notes_into_the_strict_table() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn, &MIGRATIONS[..5]).unwrap();
+        conn.execute_batch(
+            "
+            insert into notes (id, body, width, height, location_x, location_y, is_active, created_at, updated_at)
+            values ('a', 'milk', 210.5, 200, 12.25, 40, 0.0, 1, 2);
+            insert into note_revisions values ('r', 'a', 'eggs', 1);
+            ",
+        )
+        .unwrap();
+
+        migrate(&mut conn).unwrap();
+
+        let (width, location_x, is_active): (f64, f64, i64) = conn
+            .query_row(
+                "select width, location_x, is_active from notes where id = 'a'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((width, location_x, is_active), (210.5, 12.25, 0));
+        let revisions: i64 = conn
+            .query_row("select count(*) from note_revisions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(revisions, 1);
+        let indexed: String = conn
+            .query_row(
+                "select id from notes_fts where notes_fts match 'milk'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, "a");
+    }
+
+    #[test]
+    fn it_rejects_a_duplicate_note_id() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        let insert = "
+            insert into notes (id, body, width, height, location_x, location_y, is_active, created_at, updated_at)
+            values ('a', '', 200, 200, 200, 200, 1, 0, 0)
+        ";
+
+        conn.execute(insert, ()).unwrap();
+
+        assert!(conn.execute(insert, ()).is_err());
+    }
+
+    #[test]
+    fn it_refuses_a_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        let result = migrate(&mut conn);
+
+        assert!(matches!(result, Err(DbError::SchemaTooNew { .. })));
+    }
+
+    #[test]
+    fn it_rolls_back_a_failed_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "ok",
+                sql: "CREATE TABLE a (x INTEGER);",
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                sql: "CREATE TABLE b (x INTEGER); NOT SQL;",
+            },
+        ];
+
+        assert!(run(&mut conn, &migrations).is_err());
+
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert!(!table_exists(&conn, "a"));
+        assert!(!table_exists(&conn, "b"));
+    }
+}
@@ -0,0 +1,89 @@
+use std::{env, path::PathBuf};
+
+/// Environment variable that overrides the database location.
+pub const DATABASE_PATH_ENV: &str = "STICKY_DB";
+
+const APP_DIR: &str = "sticky";
+const DATABASE_FILE: &str = "database.sqlite";
+
+/// Resolves the database file in order of precedence: an explicit path
+/// (the `--db` flag), `$STICKY_DB`, then the platform data directory
+/// (`$XDG_DATA_HOME/sticky` on Linux, `~/Library/Application Support/sticky`
+/// on macOS, `%APPDATA%\sticky` on Windows). Returns `None` if there is
+/// neither a data nor a home directory to put the database in.
+pub fn resolve_database_path(explicit: Option<PathBuf>) -> Option<PathBuf> {
+    resolve(
+        explicit,
+        env::var_os(DATABASE_PATH_ENV).map(PathBuf::from),
+        default_database_path,
+    )
+}
+
+/// The database in the platform data directory, or in `~/.sticky` where
+/// there is none. Never relative to the current directory.
+pub fn default_database_path() -> Option<PathBuf> {
+    database_path_in(dirs::data_dir(), dirs::home_dir())
+}
+
+fn resolve(
+    explicit: Option<PathBuf>,
+    from_env: Option<PathBuf>,
+    default: impl FnOnce() -> Option<PathBuf>,
+) -> Option<PathBuf> {
+    [explicit, from_env]
+        .into_iter()
+        .flatten()
+        .find(|path| !path.as_os_str().is_empty())
+        .or_else(default)
+}
+
+fn database_path_in(data_dir: Option<PathBuf>, home_dir: Option<PathBuf>) -> Option<PathBuf> {
+    let dir = match (data_dir, home_dir) {
+        (Some(data_dir), _) => data_dir.join(APP_DIR),
+        (None, Some(home_dir)) => home_dir.join(format!(".{APP_DIR}")),
+        (None, None) => return None,
+    };
+    Some(dir.join(DATABASE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default() -> Option<PathBuf> {
+        Some(PathBuf::from("/data/sticky/database.sqlite"))
+    }
+
+    #[test]
+    fn it_prefers_an_explicit_path() {
+        let path = resolve(
+            Some(PathBuf::from("/tmp/notes.sqlite")),
+            Some(PathBuf::from("/tmp/env.sqlite")),
+            default,
+        );
+
+        assert_eq!(path, Some(PathBuf::from("/tmp/notes.sqlite")));
+    }
+
+    #[test]
+    fn it_falls_back_to_the_environment_then_the_default() {
+        let from_env = resolve(None, Some(PathBuf::from("/tmp/env.sqlite")), default);
+        assert_eq!(from_env, Some(PathBuf::from("/tmp/env.sqlite")));
+
+        let empty = resolve(Some(PathBuf::new()), Some(PathBuf::new()), default);
+        assert_eq!(empty, default());
+    }
+
+    #[test]
+    fn it_never_defaults_to_the_current_directory() {
+        assert_eq!(
+            database_path_in(Some("/data".into()), Some("/home/a".into())),
+            Some(PathBuf::from("/data/sticky/database.sqlite"))
+        );
+        assert_eq!(
+            database_path_in(None, Some("/home/a".into())),
+            Some(PathBuf::from("/home/a/.sticky/database.sqlite"))
+        );
+        assert_eq!(database_path_in(None, None), None);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    Db,
+    error::{DbError, Failure, Result},
+};
+
+/// How often opening is attempted while the database is busy.
+const OPEN_ATTEMPTS: u32 = 4;
+/// The wait before the first retry; it doubles with every attempt.
+const FIRST_BACKOFF: Duration = Duration::from_millis(250);
+
+/// SQLite keeps these next to the database while it is in use.
+const SIDECAR_SUFFIXES: &[&str] = &["-wal", "-shm", "-journal"];
+
+/// Opens, migrates and checks the database, retrying with exponential
+/// backoff while another process holds a lock on it.
+pub fn open_with_retry(path: &Path) -> Result<Db> {
+    retry(OPEN_ATTEMPTS, FIRST_BACKOFF, thread::sleep, || {
+        open_checked(path)
+    })
+}
+
+fn open_checked(path: &Path) -> Result<Db> {
+    let mut db = Db::open(path)?;
+    db.prepare_database()?;
+    db.quick_check()?;
+    Ok(db)
+}
+
+fn retry<T>(
+    attempts: u32,
+    first_backoff: Duration,
+    mut sleep: impl FnMut(Duration),
+    mut open: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let mut backoff = first_backoff;
+    let mut attempt = 1;
+    loop {
+        match open() {
+            Err(err) if err.failure() == Failure::Busy && attempt < attempts => {
+                sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Renames a broken database, and whatever SQLite kept next to it, out of
+/// the way so that a fresh one can be created in its place. Returns where
+/// the database went.
+pub fn move_aside(path: &Path) -> Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let suffix = format!(".broken-{stamp}");
+
+    let target = with_suffix(path, &suffix);
+    fs::rename(path, &target)?;
+    for sidecar in SIDECAR_SUFFIXES {
+        let from = with_suffix(path, sidecar);
+        if from.exists() {
+            fs::rename(&from, with_suffix(&target, sidecar))?;
+        }
+    }
+    Ok(target)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Checks that a read-only database can be used as it is.
+pub(crate) fn ensure_current(db: &Db) -> Result<()> {
+    let found = crate::migration::schema_version(&db.conn)?;
+    let required = crate::migration::latest_version();
+    if found > required {
+        return Err(DbError::SchemaTooNew {
+            found,
+            supported: required,
+        });
+    }
+    if found < required {
+        return Err(DbError::SchemaTooOld { found, required });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use rusqlite::ffi;
+
+    use super::*;
+    use crate::utils::TempDir;
+
+    fn busy() -> DbError {
+        DbError::Sqlite(rusqlite::Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_BUSY),
+            None,
+        ))
+    }
+
+    #[test]
+    fn it_retries_while_busy_with_backoff() {
+        let sleeps = RefCell::new(vec![]);
+        let mut calls = 0;
+
+        let result = retry(
+            4,
+            Duration::from_millis(10),
+            |duration| sleeps.borrow_mut().push(duration.as_millis()),
+            || {
+                calls += 1;
+                if calls < 3 { Err(busy()) } else { Ok(calls) }
+            },
+        );
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(*sleeps.borrow(), vec![10, 20]);
+    }
+
+    #[test]
+    fn it_gives_up_after_the_last_attempt() {
+        let mut calls = 0;
+
+        let result: Result<()> = retry(
+            3,
+            Duration::ZERO,
+            |_| {},
+            || {
+                calls += 1;
+                Err(busy())
+            },
+        );
+
+        assert_eq!(result.unwrap_err().failure(), Failure::Busy);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn it_does_not_retry_other_failures() {
+        let mut calls = 0;
+
+        let result: Result<()> = retry(
+            3,
+            Duration::ZERO,
+            |_| {},
+            || {
+                calls += 1;
+                Err(DbError::Corrupt("bad page".into()))
+            },
+        );
+
+        assert_eq!(result.unwrap_err().failure(), Failure::Corrupt);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn it_moves_a_broken_database_aside() {
+        let dir = TempDir::new();
+        let path = dir.join("database.sqlite");
+        fs::write(&path, "not a database").unwrap();
+        fs::write(with_suffix(&path, "-wal"), "").unwrap();
+
+        assert_eq!(
+            open_checked(&path).err().map(|err| err.failure()),
+            Some(Failure::Corrupt)
+        );
+        let moved = move_aside(&path).unwrap();
+
+        assert!(!path.exists());
+        assert!(moved.exists());
+        assert!(with_suffix(&moved, "-wal").exists());
+        assert!(open_checked(&path).is_ok());
+    }
+}
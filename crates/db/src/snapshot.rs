@@ -0,0 +1,274 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+
+use crate::{
+    Db,
+    error::{DbError, Result},
+    migration,
+};
+
+/// How the time a snapshot was taken appears in its file name.
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+const EXTENSION: &str = "sqlite";
+
+/// A copy of the database taken while the app was running.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: DateTime<Utc>,
+    /// The notes outside the trash, or `None` if the snapshot cannot be
+    /// read.
+    pub note_count: Option<usize>,
+}
+
+impl Db {
+    /// Copies the database into a new file at `path` with SQLite's online
+    /// backup API, so that other connections can go on writing meanwhile.
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        self.conn.backup(DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+}
+
+/// Takes rolling snapshots of the database at `db_path`, keeping the latest
+/// `generations` of them in the `backups` directory next to it.
+pub struct Snapshots {
+    db: Db,
+    db_path: PathBuf,
+    generations: usize,
+    /// The data version at the latest snapshot.
+    seen: Option<i64>,
+}
+
+impl Snapshots {
+    pub fn open(db_path: &Path, generations: usize) -> Result<Self> {
+        Ok(Self {
+            db: Db::open(db_path)?,
+            db_path: db_path.to_path_buf(),
+            generations,
+            seen: None,
+        })
+    }
+
+    /// Takes a snapshot unless nothing changed since the previous one, then
+    /// removes the snapshots beyond the generations kept. Returns the new
+    /// snapshot, if any.
+    pub fn take(&mut self, now: DateTime<Utc>) -> Result<Option<PathBuf>> {
+        // Only changes made through other connections count, and this one
+        // never writes.
+        let version = self
+            .db
+            .conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))?;
+        if self.seen.replace(version) == Some(version) {
+            return Ok(None);
+        }
+
+        let path = snapshot_path(&self.db_path, now);
+        fs::create_dir_all(snapshot_dir(&self.db_path))?;
+        self.db.backup_to(&path)?;
+        for (old, _) in taken(&self.db_path)?.into_iter().skip(self.generations) {
+            fs::remove_file(old)?;
+        }
+        Ok(Some(path))
+    }
+}
+
+/// Where the snapshots of the database at `db_path` are kept.
+pub fn snapshot_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("backups")
+}
+
+/// The snapshots of the database at `db_path`, newest first.
+pub fn list_snapshots(db_path: &Path) -> Result<Vec<Snapshot>> {
+    Ok(taken(db_path)?
+        .into_iter()
+        .map(|(path, taken_at)| Snapshot {
+            note_count: count_notes(&path).ok(),
+            path,
+            taken_at,
+        })
+        .collect())
+}
+
+/// Replaces the database at `db_path` with `snapshot`, through the backup
+/// API so that connections that have the database open see the change. The
+/// database is snapshotted first, so that the restore can be undone; returns
+/// that snapshot.
+pub fn restore_snapshot(db_path: &Path, snapshot: &Path, now: DateTime<Utc>) -> Result<PathBuf> {
+    let source = open_snapshot(snapshot)?;
+    source.quick_check()?;
+    let found = migration::schema_version(&source.conn)?;
+    if found > migration::latest_version() {
+        return Err(DbError::SchemaTooNew {
+            found,
+            supported: migration::latest_version(),
+        });
+    }
+    drop(source);
+
+    let mut db = Db::open(db_path)?;
+    let undo = snapshot_path(db_path, now);
+    fs::create_dir_all(snapshot_dir(db_path))?;
+    db.backup_to(&undo)?;
+    db.conn.restore(
+        DatabaseName::Main,
+        snapshot,
+        None::<fn(rusqlite::backup::Progress)>,
+    )?;
+    // A snapshot from before an upgrade is brought up to date.
+    db.prepare_database()?;
+    Ok(undo)
+}
+
+fn snapshot_path(db_path: &Path, taken_at: DateTime<Utc>) -> PathBuf {
+    let name = format!(
+        "{}-{}.{EXTENSION}",
+        stem(db_path),
+        taken_at.format(STAMP_FORMAT)
+    );
+    snapshot_dir(db_path).join(name)
+}
+
+/// The snapshot files of the database at `db_path` and when they were
+/// taken, newest first.
+fn taken(db_path: &Path) -> Result<Vec<(PathBuf, DateTime<Utc>)>> {
+    let entries = match fs::read_dir(snapshot_dir(db_path)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let prefix = format!("{}-", stem(db_path));
+    let mut snapshots = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let stamp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|name| name.strip_suffix(&format!(".{EXTENSION}")));
+        let taken_at = stamp
+            .and_then(|stamp| NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok())
+            .map(|taken_at| taken_at.and_utc());
+        if let Some(taken_at) = taken_at {
+            snapshots.push((path, taken_at));
+        }
+    }
+    snapshots.sort_by_key(|(_, taken_at)| Reverse(*taken_at));
+    Ok(snapshots)
+}
+
+fn stem(db_path: &Path) -> String {
+    db_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "database".to_string())
+}
+
+fn open_snapshot(path: &Path) -> Result<Db> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    Ok(Db { conn })
+}
+
+fn count_notes(path: &Path) -> Result<usize> {
+    let count = open_snapshot(path)?.conn.query_row(
+        "select count(*) from notes where deleted_at is null",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::utils::TempDir;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(1_700_000_000_000).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    fn add_note(db_path: &Path, id: &str) {
+        let mut db = Db::open(db_path).unwrap();
+        db.prepare_database().unwrap();
+        db.conn
+            .execute(
+                "insert into notes (id, body, width, height, location_x, location_y, is_active, created_at, updated_at)
+                 values (?1, 'milk', 200, 200, 0, 0, 1, 0, 0)",
+                [id],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn it_takes_snapshots_only_after_changes() {
+        let dir = TempDir::new();
+        let db_path = dir.join("database.sqlite");
+        add_note(&db_path, "a");
+        let mut snapshots = Snapshots::open(&db_path, 5).unwrap();
+
+        assert!(snapshots.take(at(0)).unwrap().is_some());
+        assert!(snapshots.take(at(1)).unwrap().is_none());
+        add_note(&db_path, "b");
+        assert!(snapshots.take(at(2)).unwrap().is_some());
+
+        let listed = list_snapshots(&db_path).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].taken_at, at(2));
+        assert_eq!(listed[0].note_count, Some(2));
+        assert_eq!(listed[1].note_count, Some(1));
+    }
+
+    #[test]
+    fn it_keeps_only_the_latest_generations() {
+        let dir = TempDir::new();
+        let db_path = dir.join("database.sqlite");
+        let mut snapshots = Snapshots::open(&db_path, 2).unwrap();
+
+        for (minute, id) in ["a", "b", "c"].into_iter().enumerate() {
+            add_note(&db_path, id);
+            snapshots.take(at(minute as i64)).unwrap();
+        }
+
+        let taken_at = list_snapshots(&db_path)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.taken_at)
+            .collect::<Vec<_>>();
+        assert_eq!(taken_at, vec![at(2), at(1)]);
+    }
+
+    #[test]
+    fn it_restores_a_snapshot_and_keeps_the_replaced_database() {
+        let dir = TempDir::new();
+        let db_path = dir.join("database.sqlite");
+        add_note(&db_path, "a");
+        let snapshot = Snapshots::open(&db_path, 5)
+            .unwrap()
+            .take(at(0))
+            .unwrap()
+            .unwrap();
+        add_note(&db_path, "b");
+        let open = Db::open(&db_path).unwrap();
+
+        let undo = restore_snapshot(&db_path, &snapshot, at(1)).unwrap();
+
+        let count: usize = open
+            .conn
+            .query_row("select count(*) from notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(count_notes(&undo).unwrap(), 2);
+    }
+}
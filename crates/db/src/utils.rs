@@ -3,3 +3,39 @@ use ulid::Ulid;
 pub fn gen_id() -> String {
     Ulid::new().to_string()
 }
+
+/// A fresh directory under the system temp directory for a test, removed
+/// with everything in it when dropped, even if the test panics.
+#[cfg(any(test, feature = "test-utils"))]
+pub struct TempDir(std::path::PathBuf);
+
+#[cfg(any(test, feature = "test-utils"))]
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("sticky-{}", gen_id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<std::path::Path>) -> std::path::PathBuf {
+        self.0.join(path)
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Default for TempDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
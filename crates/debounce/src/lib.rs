@@ -1,30 +1,109 @@
-use std::time::{Duration, Instant};
-pub struct Bouncer<T> {
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+mod throttle;
+#[cfg(feature = "gpui")]
+pub mod timer;
+
+use clock::{Clock, SystemClock};
+pub use throttle::Throttle;
+
+/// Something that holds back calls until a deadline and has to be polled
+/// once it passes.
+pub trait Deferred {
+    /// Time left until the next [`Deferred::poll`] has work to do, if any.
+    fn remaining(&self) -> Option<Duration>;
+
+    /// Runs whatever is due. Returns whether a call ran.
+    fn poll(&mut self) -> bool;
+}
+
+/// Which end of a burst of calls actually runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Edge {
+    /// Run the first call right away and drop the rest of the burst.
+    Leading,
+    /// Run only the last call, `delay` after the burst ends.
+    #[default]
+    Trailing,
+    /// Run the first call right away, and the last one once the burst ends
+    /// if there was more than one call.
+    Both,
+}
+
+/// Debouncer for bursts of calls, such as keystrokes.
+///
+/// Every call pushes the deadline back by `delay`. Depending on the
+/// [`Edge`], the first and/or the last call of a burst runs; trailing calls
+/// run from [`Bouncer::poll`] once the deadline has passed. Whatever is still
+/// pending when the bouncer is dropped runs on drop, so the final value is
+/// never lost.
+pub struct Bouncer<T = (), A = ()> {
     pub delay: Duration,
-    last_run: Option<Instant>,
-    func: Option<fn() -> T>,
+    edge: Edge,
+    clock: Rc<dyn Clock>,
+    deadline: Option<Instant>,
+    pending: Option<Pending<T, A>>,
+    func: Option<Box<dyn FnMut(A) -> T>>,
     result: Option<T>,
 }
 
-impl<T> Bouncer<T> {
+pub(crate) enum Pending<T, A> {
+    /// A one-off closure.
+    Call(Box<dyn FnOnce() -> T>),
+    /// Arguments for the function bound with `with_func`.
+    Bound(A),
+}
+
+impl<T, A> Pending<T, A> {
+    /// Runs the call. Returns `None` for arguments without a bound function.
+    pub(crate) fn run(self, func: Option<&mut Box<dyn FnMut(A) -> T>>) -> Option<T> {
+        match self {
+            Pending::Call(call) => Some(call()),
+            Pending::Bound(args) => func.map(|func| func(args)),
+        }
+    }
+}
+
+impl<T: 'static, A: 'static> Bouncer<T, A> {
     pub fn new(delay: Duration) -> Self {
         Self {
             delay,
-            last_run: None,
+            edge: Edge::default(),
+            clock: Rc::new(SystemClock),
+            deadline: None,
+            pending: None,
             func: None,
             result: None,
         }
     }
 
-    pub fn with_func(mut self, func: fn() -> T) -> Self {
-        self.func = Some(func);
+    pub fn with_edge(mut self, edge: Edge) -> Self {
+        self.edge = edge;
         self
     }
 
-    pub fn execute(&mut self) {
-        if let Some(func) = self.func {
-            let result = self.debounce(func);
-            self.result = result;
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Rc::new(clock);
+        self
+    }
+
+    pub fn with_func(mut self, func: impl FnMut(A) -> T + 'static) -> Self {
+        self.func = Some(Box::new(func));
+        self
+    }
+
+    pub fn edge(&self) -> Edge {
+        self.edge
+    }
+
+    /// Calls the function bound with `with_func` with `args`, subject to
+    /// debouncing. Does nothing if no function is bound.
+    pub fn call(&mut self, args: A) {
+        if self.func.is_some() {
+            self.schedule(Pending::Bound(args));
         }
     }
 
@@ -32,76 +111,261 @@ impl<T> Bouncer<T> {
         self.result.as_ref()
     }
 
-    pub fn debounce(&mut self, func: fn() -> T) -> Option<T> {
-        match self.last_run {
-            Some(last_run) => {
-                let now = Instant::now();
+    /// Takes the result of the latest call, such as to handle its error.
+    pub fn take_result(&mut self) -> Option<T> {
+        self.result.take()
+    }
 
-                if now.duration_since(last_run) > self.delay {
-                    self.last_run = Some(Instant::now());
+    /// Calls `func`, subject to debouncing.
+    pub fn debounce(&mut self, func: impl FnOnce() -> T + 'static) {
+        self.schedule(Pending::Call(Box::new(func)));
+    }
 
-                    Some(func())
-                } else {
-                    None
-                }
-            }
-            None => {
-                self.last_run = Some(Instant::now());
-                Some(func())
+    fn schedule(&mut self, pending: Pending<T, A>) {
+        let now = self.clock.now();
+        let in_burst = self.deadline.is_some_and(|deadline| now < deadline);
+        self.deadline = Some(now + self.delay);
+
+        match (self.edge, in_burst) {
+            (Edge::Leading | Edge::Both, false) => {
+                // A leftover trailing call belongs to the previous burst.
+                self.flush_pending();
+                self.run(pending);
             }
+            (Edge::Leading, true) => {}
+            (Edge::Trailing | Edge::Both, _) => self.pending = Some(pending),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// When the current burst ends, if one is in progress.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Ends the burst if its deadline has passed, running the trailing call
+    /// if there is one. Returns whether a call ran.
+    pub fn poll(&mut self) -> bool {
+        match self.deadline {
+            Some(deadline) if self.clock.now() >= deadline => self.flush(),
+            _ => false,
         }
     }
 
+    /// Ends the burst right away, running the pending call if there is one.
+    /// Returns whether a call ran.
+    pub fn flush(&mut self) -> bool {
+        self.deadline = None;
+        self.flush_pending()
+    }
+
+    fn flush_pending(&mut self) -> bool {
+        match self.pending.take() {
+            Some(pending) => self.run(pending),
+            None => false,
+        }
+    }
+
+    /// Ends the burst and drops the pending call without running it.
+    pub fn cancel(&mut self) {
+        self.deadline = None;
+        self.pending = None;
+    }
+
+    fn run(&mut self, pending: Pending<T, A>) -> bool {
+        let result = pending.run(self.func.as_mut());
+        let ran = result.is_some();
+        if ran {
+            self.result = result;
+        }
+        ran
+    }
+
     pub fn reset(&mut self) {
-        self.last_run = None;
+        self.result = None;
+    }
+}
+
+impl<T: 'static> Bouncer<T, ()> {
+    /// Calls the function bound with `with_func`, subject to debouncing.
+    pub fn execute(&mut self) {
+        self.call(());
+    }
+}
+
+impl<T: 'static, A: 'static> Deferred for Bouncer<T, A> {
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(self.clock.now()))
+    }
+
+    fn poll(&mut self) -> bool {
+        Bouncer::poll(self)
+    }
+}
+
+impl<T, A> Drop for Bouncer<T, A> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.run(self.func.as_mut());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use clock::ManualClock;
+
     use super::*;
 
+    pub(crate) fn recorder() -> (Rc<RefCell<Vec<i32>>>, impl FnMut(i32) + 'static) {
+        let calls = Rc::new(RefCell::new(vec![]));
+        let record = {
+            let calls = calls.clone();
+            move |value| calls.borrow_mut().push(value)
+        };
+        (calls, record)
+    }
+
+    fn bouncer(edge: Edge, record: impl FnMut(i32) + 'static) -> (ManualClock, Bouncer<(), i32>) {
+        let clock = ManualClock::new();
+        let bouncer = Bouncer::new(Duration::from_millis(100))
+            .with_edge(edge)
+            .with_clock(clock.clone())
+            .with_func(record);
+        (clock, bouncer)
+    }
+
     #[test]
     fn it_works() {
         let delay = Duration::from_secs(1);
-        let mut bouncer = Bouncer::new(delay);
+        let mut bouncer = Bouncer::<_>::new(delay);
 
-        let result = bouncer.debounce(|| 5 + 6);
+        bouncer.debounce(|| 5 + 6);
+        bouncer.flush();
 
+        let result = bouncer.get_result();
         assert!(result.is_some());
-        assert_eq!(result.unwrap(), 11);
+        assert_eq!(*result.unwrap(), 11);
     }
 
     #[test]
     fn it_binds_internal_func() {
-        let func = || 5 + 6;
+        let mut count = 0;
+        let func = move |()| {
+            count += 1;
+            count
+        };
         let delay = Duration::from_secs(1);
 
         let mut bouncer = Bouncer::new(delay).with_func(func);
 
         assert!(bouncer.get_result().is_none());
         bouncer.execute();
-        assert!(bouncer.get_result().is_some());
+        bouncer.execute();
+        assert!(bouncer.get_result().is_none());
+        bouncer.flush();
+        assert_eq!(bouncer.get_result(), Some(&1));
     }
 
     #[test]
     fn it_debounces() {
-        let delay = Duration::from_millis(100);
-        let mut bouncer = Bouncer::new(delay);
+        let (calls, record) = recorder();
+        let (clock, mut bouncer) = bouncer(Edge::Trailing, record);
+
+        for value in [1, 2, 3] {
+            bouncer.call(value);
+        }
 
-        let func = || 5 + 6;
+        clock.advance(Duration::from_millis(99));
+        assert!(!bouncer.poll());
+        clock.advance(Duration::from_millis(1));
+        assert!(bouncer.poll());
+        assert!(!bouncer.poll());
+        assert_eq!(*calls.borrow(), vec![3]);
+    }
 
-        let result1 = bouncer.debounce(func);
-        let result2 = bouncer.debounce(func);
+    #[test]
+    fn it_extends_the_deadline_on_every_call() {
+        let (calls, record) = recorder();
+        let (clock, mut bouncer) = bouncer(Edge::Trailing, record);
 
-        std::thread::sleep(Duration::from_millis(101));
+        bouncer.call(1);
+        clock.advance(Duration::from_millis(80));
+        bouncer.call(2);
 
-        let result3 = bouncer.debounce(func);
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(bouncer.remaining(), Some(Duration::from_millis(60)));
+        assert!(!bouncer.poll());
+        clock.advance(Duration::from_millis(60));
+        assert!(bouncer.poll());
+        assert_eq!(*calls.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn it_runs_the_leading_call() {
+        let (calls, record) = recorder();
+        let (clock, mut bouncer) = bouncer(Edge::Leading, record);
+
+        for value in [1, 2, 3] {
+            bouncer.call(value);
+        }
+        assert_eq!(*calls.borrow(), vec![1]);
+        clock.advance(Duration::from_millis(100));
+        assert!(!bouncer.poll());
+
+        clock.advance(Duration::from_millis(50));
+        bouncer.call(4);
+        assert_eq!(*calls.borrow(), vec![1, 4]);
+    }
+
+    #[test]
+    fn it_runs_both_edges() {
+        let (calls, record) = recorder();
+        let (clock, mut bouncer) = bouncer(Edge::Both, record);
+
+        bouncer.call(1);
+        clock.advance(Duration::from_millis(100));
+        assert!(!bouncer.poll());
+
+        clock.advance(Duration::from_millis(100));
+        for value in [2, 3, 4] {
+            bouncer.call(value);
+        }
+        clock.advance(Duration::from_millis(100));
+        assert!(bouncer.poll());
+
+        assert_eq!(*calls.borrow(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn it_cancels() {
+        let (calls, record) = recorder();
+        let (_, mut bouncer) = bouncer(Edge::Trailing, record);
+
+        bouncer.call(1);
+        bouncer.cancel();
+
+        assert!(!bouncer.is_pending());
+        assert!(!bouncer.flush());
+        drop(bouncer);
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn it_flushes_on_drop() {
+        let (calls, record) = recorder();
+
+        {
+            let (_, mut bouncer) = bouncer(Edge::Trailing, record);
+            bouncer.call(1);
+        }
 
-        assert!(result1.is_some());
-        assert_eq!(result1.unwrap(), 11);
-        assert!(result2.is_none());
-        assert!(result3.is_some());
-        assert_eq!(result3.unwrap(), 11);
+        assert_eq!(*calls.borrow(), vec![1]);
     }
 }
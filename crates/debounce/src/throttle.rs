@@ -0,0 +1,220 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use clock::{Clock, SystemClock};
+
+use crate::{Deferred, Pending};
+
+/// Runs at most one call per `interval`, such as for a window being dragged.
+///
+/// A call outside the interval runs right away. Calls inside it replace each
+/// other and the last one runs from [`Throttle::poll`] once the interval is
+/// over, so the final value always lands. Whatever is still pending when the
+/// throttle is dropped runs on drop.
+pub struct Throttle<T = (), A = ()> {
+    pub interval: Duration,
+    clock: Rc<dyn Clock>,
+    last_run: Option<Instant>,
+    pending: Option<Pending<T, A>>,
+    func: Option<Box<dyn FnMut(A) -> T>>,
+    result: Option<T>,
+}
+
+impl<T: 'static, A: 'static> Throttle<T, A> {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            clock: Rc::new(SystemClock),
+            last_run: None,
+            pending: None,
+            func: None,
+            result: None,
+        }
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Rc::new(clock);
+        self
+    }
+
+    pub fn with_func(mut self, func: impl FnMut(A) -> T + 'static) -> Self {
+        self.func = Some(Box::new(func));
+        self
+    }
+
+    /// Calls the function bound with `with_func` with `args`, subject to
+    /// throttling. Does nothing if no function is bound.
+    pub fn call(&mut self, args: A) {
+        if self.func.is_some() {
+            self.schedule(Pending::Bound(args));
+        }
+    }
+
+    /// Calls `func`, subject to throttling.
+    pub fn throttle(&mut self, func: impl FnOnce() -> T + 'static) {
+        self.schedule(Pending::Call(Box::new(func)));
+    }
+
+    fn schedule(&mut self, pending: Pending<T, A>) {
+        if self
+            .deadline()
+            .is_some_and(|deadline| self.clock.now() < deadline)
+        {
+            self.pending = Some(pending);
+        } else {
+            self.run(pending);
+        }
+    }
+
+    pub fn get_result(&mut self) -> Option<&T> {
+        self.result.as_ref()
+    }
+
+    /// Takes the result of the latest call, such as to handle its error.
+    pub fn take_result(&mut self) -> Option<T> {
+        self.result.take()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// When the next call may run, if the last one was recent.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.last_run.map(|last_run| last_run + self.interval)
+    }
+
+    /// Runs the trailing call if the interval is over. Returns whether it
+    /// ran.
+    pub fn poll(&mut self) -> bool {
+        let due = self
+            .deadline()
+            .is_none_or(|deadline| self.clock.now() >= deadline);
+        due && self.flush()
+    }
+
+    /// Runs the trailing call right away. Returns whether there was one.
+    pub fn flush(&mut self) -> bool {
+        match self.pending.take() {
+            Some(pending) => self.run(pending),
+            None => false,
+        }
+    }
+
+    /// Drops the trailing call without running it.
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    fn run(&mut self, pending: Pending<T, A>) -> bool {
+        self.last_run = Some(self.clock.now());
+        let result = pending.run(self.func.as_mut());
+        let ran = result.is_some();
+        if ran {
+            self.result = result;
+        }
+        ran
+    }
+}
+
+impl<T: 'static> Throttle<T, ()> {
+    /// Calls the function bound with `with_func`, subject to throttling.
+    pub fn execute(&mut self) {
+        self.call(());
+    }
+}
+
+impl<T: 'static, A: 'static> Deferred for Throttle<T, A> {
+    fn remaining(&self) -> Option<Duration> {
+        self.pending.as_ref()?;
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(self.clock.now()))
+    }
+
+    fn poll(&mut self) -> bool {
+        Throttle::poll(self)
+    }
+}
+
+impl<T, A> Drop for Throttle<T, A> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.run(self.func.as_mut());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clock::ManualClock;
+
+    use super::*;
+    use crate::tests::recorder;
+
+    fn throttle(record: impl FnMut(i32) + 'static) -> (ManualClock, Throttle<(), i32>) {
+        let clock = ManualClock::new();
+        let throttle = Throttle::new(Duration::from_millis(100))
+            .with_clock(clock.clone())
+            .with_func(record);
+        (clock, throttle)
+    }
+
+    #[test]
+    fn it_runs_the_first_call_right_away() {
+        let (calls, record) = recorder();
+        let (_, mut throttle) = throttle(record);
+
+        throttle.call(1);
+
+        assert!(!throttle.is_pending());
+        assert_eq!(*calls.borrow(), vec![1]);
+    }
+
+    #[test]
+    fn it_runs_at_most_once_per_interval() {
+        let (calls, record) = recorder();
+        let (clock, mut throttle) = throttle(record);
+
+        for value in 0..10 {
+            throttle.call(value);
+            clock.advance(Duration::from_millis(25));
+            throttle.poll();
+        }
+
+        assert_eq!(*calls.borrow(), vec![0, 3, 7]);
+        assert!(throttle.is_pending());
+    }
+
+    #[test]
+    fn it_keeps_the_trailing_call() {
+        let (calls, record) = recorder();
+        let (clock, mut throttle) = throttle(record);
+
+        throttle.call(1);
+        throttle.call(2);
+        throttle.call(3);
+        assert_eq!(throttle.remaining(), Some(Duration::from_millis(100)));
+
+        clock.advance(Duration::from_millis(50));
+        assert!(!throttle.poll());
+        clock.advance(Duration::from_millis(50));
+        assert!(throttle.poll());
+
+        assert_eq!(*calls.borrow(), vec![1, 3]);
+    }
+
+    #[test]
+    fn it_flushes_on_drop() {
+        let (calls, record) = recorder();
+
+        {
+            let (_, mut throttle) = throttle(record);
+            throttle.call(1);
+            throttle.call(2);
+        }
+
+        assert_eq!(*calls.borrow(), vec![1, 2]);
+    }
+}
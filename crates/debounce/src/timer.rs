@@ -0,0 +1,46 @@
+use gpui::{Context, Task};
+
+use crate::Deferred;
+
+/// Drives a [`Bouncer`](crate::Bouncer) or [`Throttle`](crate::Throttle)
+/// owned by a gpui entity from the background executor, so trailing calls run
+/// without anything having to poll by hand.
+#[derive(Default)]
+pub struct Timer {
+    task: Option<Task<()>>,
+}
+
+impl Timer {
+    /// Wakes up once `deferred` has work due and polls it. Call this after
+    /// every call to it; rescheduling drops the previous timer.
+    ///
+    /// `deferred` picks the bouncer or throttle out of the entity, and
+    /// `on_run` is called if polling ran a call, such as to check its result.
+    pub fn schedule<E, D>(
+        &mut self,
+        cx: &mut Context<E>,
+        deferred: impl Fn(&mut E) -> &mut D + 'static,
+        on_run: impl FnOnce(&mut E, &mut Context<E>) + 'static,
+    ) where
+        E: 'static,
+        D: Deferred,
+    {
+        self.task = Some(cx.spawn(async move |this, cx| {
+            let Ok(Some(remaining)) = this.update(cx, |this, _| deferred(this).remaining()) else {
+                return;
+            };
+            cx.background_executor().timer(remaining).await;
+            this.update(cx, |this, cx| {
+                if deferred(this).poll() {
+                    on_run(this, cx);
+                }
+            })
+            .ok();
+        }));
+    }
+
+    /// Drops the scheduled wake-up, if any.
+    pub fn cancel(&mut self) {
+        self.task = None;
+    }
+}
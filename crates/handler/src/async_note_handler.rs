@@ -0,0 +1,262 @@
+use std::{
+    future::Future,
+    io,
+    path::Path,
+    pin::Pin,
+    sync::mpsc,
+    task::{self, Poll},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures::channel::oneshot;
+use kernel::{
+    error::{KernelError, Result},
+    model::note::{NoteSortKey, UpdateNoteBodyEvent, UpdateNoteBoundsEvent},
+};
+
+use crate::{
+    model::{
+        diff::DiffLine,
+        import::{ImportReport, OnConflict},
+        note::Note,
+        revision::Revision,
+        search_result::SearchResult,
+    },
+    note_handler::NoteHandler,
+};
+
+type Job = Box<dyn FnOnce(&NoteHandler) + Send>;
+
+/// A [`NoteHandler`] that lives on a thread of its own, so that SQLite never
+/// blocks the UI thread. Requests run one at a time in the order they were
+/// made, so a request sees the changes of every request made before it.
+#[derive(Clone)]
+pub struct AsyncNoteHandler {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl AsyncNoteHandler {
+    /// Starts the worker thread. `open` builds the handler on that thread,
+    /// since a handler cannot move between threads once it is built. The
+    /// thread stops once every clone of the returned handle is dropped.
+    pub fn spawn(open: impl FnOnce() -> NoteHandler + Send + 'static) -> io::Result<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("sticky-db".to_string())
+            .spawn(move || {
+                let handler = open();
+                for job in queue {
+                    job(&handler);
+                }
+            })?;
+        Ok(Self { jobs })
+    }
+
+    /// Runs `request` on the worker thread. Several calls that have to see
+    /// the same state belong in one request.
+    pub fn run<T: Send + 'static>(
+        &self,
+        request: impl FnOnce(&NoteHandler) -> Result<T> + Send + 'static,
+    ) -> Reply<T> {
+        let (sender, receiver) = oneshot::channel();
+        let sent = self.jobs.send(Box::new(move |handler| {
+            // The caller may have stopped waiting for the reply.
+            let _ = sender.send(request(handler));
+        }));
+        Reply {
+            receiver: sent.is_ok().then_some(receiver),
+        }
+    }
+
+    pub fn now(&self) -> Reply<DateTime<Utc>> {
+        self.run(|handler| Ok(handler.now()))
+    }
+
+    pub fn create_note(&self) -> Reply<Note> {
+        self.run(|handler| handler.create_note())
+    }
+
+    pub fn create_note_with_body(&self, body: &str) -> Reply<Note> {
+        let body = body.to_string();
+        self.run(move |handler| handler.create_note_with_body(&body))
+    }
+
+    pub fn get_all(&self, sort: NoteSortKey) -> Reply<Vec<Note>> {
+        self.run(move |handler| handler.get_all(sort))
+    }
+
+    pub fn get_by_id(&self, id: &str) -> Reply<Option<Note>> {
+        let id = id.to_string();
+        self.run(move |handler| handler.get_by_id(&id))
+    }
+
+    pub fn search_notes(&self, query: &str) -> Reply<Vec<SearchResult>> {
+        let query = query.to_string();
+        self.run(move |handler| handler.search_notes(&query))
+    }
+
+    pub fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Reply<()> {
+        self.run(move |handler| handler.update_note_body(event))
+    }
+
+    pub fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Reply<()> {
+        self.run(move |handler| handler.update_note_bounds(event))
+    }
+
+    pub fn set_note_active(&self, id: &str, is_active: bool) -> Reply<()> {
+        let id = id.to_string();
+        self.run(move |handler| handler.set_note_active(&id, is_active))
+    }
+
+    pub fn show_all_notes(&self) -> Reply<Vec<Note>> {
+        self.run(|handler| handler.show_all_notes())
+    }
+
+    pub fn toggle_note_active(&self, id: &str) -> Reply<()> {
+        let id = id.to_string();
+        self.run(move |handler| handler.toggle_note_active(&id))
+    }
+
+    pub fn delete_note(&self, id: &str) -> Reply<()> {
+        let id = id.to_string();
+        self.run(move |handler| handler.delete_note(&id))
+    }
+
+    pub fn get_trash(&self) -> Reply<Vec<Note>> {
+        self.run(|handler| handler.get_trash())
+    }
+
+    pub fn restore_note(&self, id: &str) -> Reply<Note> {
+        let id = id.to_string();
+        self.run(move |handler| handler.restore_note(&id))
+    }
+
+    pub fn purge_note(&self, id: &str) -> Reply<()> {
+        let id = id.to_string();
+        self.run(move |handler| handler.purge_note(&id))
+    }
+
+    pub fn purge_trash(&self, retention: Duration) -> Reply<usize> {
+        self.run(move |handler| handler.purge_trash(retention))
+    }
+
+    pub fn snapshot_note(&self, id: &str) -> Reply<()> {
+        let id = id.to_string();
+        self.run(move |handler| handler.snapshot_note(&id))
+    }
+
+    pub fn get_revisions(&self, note_id: &str) -> Reply<Vec<Revision>> {
+        let note_id = note_id.to_string();
+        self.run(move |handler| handler.get_revisions(&note_id))
+    }
+
+    pub fn restore_revision(&self, revision_id: &str) -> Reply<Note> {
+        let revision_id = revision_id.to_string();
+        self.run(move |handler| handler.restore_revision(&revision_id))
+    }
+
+    pub fn diff_revisions(&self, from_id: &str, to_id: &str) -> Reply<Vec<DiffLine>> {
+        let (from_id, to_id) = (from_id.to_string(), to_id.to_string());
+        self.run(move |handler| handler.diff_revisions(&from_id, &to_id))
+    }
+
+    pub fn diff_with_current(&self, revision_id: &str) -> Reply<Vec<DiffLine>> {
+        let revision_id = revision_id.to_string();
+        self.run(move |handler| handler.diff_with_current(&revision_id))
+    }
+
+    pub fn data_version(&self) -> Reply<i64> {
+        self.run(|handler| handler.data_version())
+    }
+
+    pub fn backup_to(&self, path: &Path) -> Reply<usize> {
+        let path = path.to_path_buf();
+        self.run(move |handler| handler.backup_to(&path))
+    }
+
+    pub fn restore_from(&self, path: &Path, on_conflict: OnConflict) -> Reply<ImportReport> {
+        let path = path.to_path_buf();
+        self.run(move |handler| handler.restore_from(&path, on_conflict))
+    }
+}
+
+/// The eventual result of a request made to an [`AsyncNoteHandler`]. The
+/// request runs whether or not the reply is awaited.
+#[must_use = "the request runs anyway, but its error is lost unless the reply is awaited"]
+pub struct Reply<T> {
+    /// `None` if the worker had already stopped when the request was made.
+    receiver: Option<oneshot::Receiver<Result<T>>>,
+}
+
+impl<T> Future for Reply<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let Some(receiver) = self.receiver.as_mut() else {
+            return Poll::Ready(Err(worker_stopped()));
+        };
+        Pin::new(receiver)
+            .poll(cx)
+            .map(|reply| reply.unwrap_or_else(|_| Err(worker_stopped())))
+    }
+}
+
+fn worker_stopped() -> KernelError {
+    KernelError::Storage("the database thread has stopped".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use adapter::repository_impl::note_repository_impl::NoteRepositoryImpl;
+    use db::Db;
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn handler() -> AsyncNoteHandler {
+        AsyncNoteHandler::spawn(|| {
+            NoteHandler::new(Rc::new(NoteRepositoryImpl::new(
+                Db::open_in_memory().unwrap().conn,
+            )))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn it_runs_requests_in_order() {
+        let handler = handler();
+
+        let created = handler.create_note();
+        let all = handler.get_all(NoteSortKey::default());
+
+        let id = block_on(created).unwrap().id;
+        let ids = block_on(all)
+            .unwrap()
+            .into_iter()
+            .map(|note| note.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![id]);
+    }
+
+    #[test]
+    fn it_reports_errors_of_a_request() {
+        let handler = handler();
+
+        let result = block_on(handler.toggle_note_active("missing"));
+
+        assert!(matches!(result, Err(KernelError::NotFound { .. })));
+    }
+
+    #[test]
+    fn it_fails_once_the_worker_has_stopped() {
+        let handler = AsyncNoteHandler::spawn(|| panic!("no database")).unwrap();
+
+        let result = block_on(handler.create_note());
+
+        assert!(matches!(result, Err(KernelError::Storage(_))));
+    }
+}
@@ -1,2 +1,3 @@
+pub mod async_note_handler;
 pub mod model;
 pub mod note_handler;
@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{import::ImportedNote, note::Note};
+
+/// Tells backups apart from other JSON files.
+pub const BACKUP_FORMAT: &str = "sticky-backup";
+/// The version of the backup format written by this build. Bump it when a
+/// change would be misread by older builds, and upgrade the older versions
+/// in [`Backup::from_json`].
+pub const BACKUP_VERSION: u32 = 1;
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Every note, trashed ones included, and where they came from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    /// The database schema the notes were read from.
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub notes: Vec<BackupNote>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackupNote {
+    pub id: String,
+    pub body: String,
+    pub width: f32,
+    pub height: f32,
+    pub location_x: f32,
+    pub location_y: f32,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("the file is not a sticky backup")]
+    NotABackup,
+    #[error(
+        "backup format version {found} is newer than the version {supported} supported by this build"
+    )]
+    TooNew { found: u64, supported: u32 },
+    #[error("backup format version {0} is not supported")]
+    Unsupported(u64),
+    #[error("the backup holds note {0} more than once")]
+    DuplicateNote(String),
+    #[error("the backup is malformed: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+impl Backup {
+    pub fn new(notes: Vec<Note>, schema_version: u32, exported_at: DateTime<Utc>) -> Self {
+        Self {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            app_version: APP_VERSION.to_string(),
+            schema_version,
+            exported_at,
+            notes: notes.into_iter().map(BackupNote::from).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a backup is always valid JSON")
+    }
+
+    /// Reads a backup of this or an older format version.
+    pub fn from_json(text: &str) -> Result<Self, BackupError> {
+        let value: Value = serde_json::from_str(text)?;
+        if value["format"] != BACKUP_FORMAT {
+            return Err(BackupError::NotABackup);
+        }
+        let version = value["version"].as_u64().ok_or(BackupError::NotABackup)?;
+        if version > u64::from(BACKUP_VERSION) {
+            return Err(BackupError::TooNew {
+                found: version,
+                supported: BACKUP_VERSION,
+            });
+        }
+        let value = upgrade(value, version)?;
+
+        let backup: Self = serde_json::from_value(value)?;
+        let mut ids = HashSet::new();
+        if let Some(note) = backup.notes.iter().find(|note| !ids.insert(&note.id)) {
+            return Err(BackupError::DuplicateNote(note.id.clone()));
+        }
+        Ok(backup)
+    }
+}
+
+/// Brings a backup of format `version` up to [`BACKUP_VERSION`], one
+/// version at a time.
+fn upgrade(value: Value, version: u64) -> Result<Value, BackupError> {
+    match version {
+        1 => Ok(value),
+        _ => Err(BackupError::Unsupported(version)),
+    }
+}
+
+impl From<Note> for BackupNote {
+    fn from(note: Note) -> Self {
+        Self {
+            id: note.id,
+            body: note.body,
+            width: note.width,
+            height: note.height,
+            location_x: note.location_x,
+            location_y: note.location_y,
+            is_active: note.is_active,
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+            closed_at: note.closed_at,
+            deleted_at: note.deleted_at,
+        }
+    }
+}
+
+impl From<BackupNote> for ImportedNote {
+    fn from(note: BackupNote) -> Self {
+        Self {
+            id: Some(note.id),
+            body: note.body,
+            width: Some(note.width),
+            height: Some(note.height),
+            location_x: Some(note.location_x),
+            location_y: Some(note.location_y),
+            is_active: Some(note.is_active),
+            created_at: Some(note.created_at),
+            updated_at: Some(note.updated_at),
+            closed_at: note.closed_at,
+            deleted_at: note.deleted_at,
+            left_out: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup() -> Backup {
+        let at = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let note = Note {
+            id: "a".into(),
+            body: "milk".into(),
+            width: 200.,
+            height: 200.,
+            location_x: 0.,
+            location_y: 0.,
+            is_active: true,
+            created_at: at,
+            updated_at: at,
+            closed_at: None,
+            deleted_at: Some(at),
+        };
+        Backup::new(vec![note], 6, at)
+    }
+
+    #[test]
+    fn it_round_trips_backups() {
+        let backup = backup();
+
+        assert_eq!(Backup::from_json(&backup.to_json()).unwrap(), backup);
+    }
+
+    #[test]
+    fn it_rejects_other_files_and_newer_versions() {
+        assert!(matches!(
+            Backup::from_json(r#"{"notes":[]}"#),
+            Err(BackupError::NotABackup)
+        ));
+
+        let mut newer = backup();
+        newer.version = BACKUP_VERSION + 1;
+        assert!(matches!(
+            Backup::from_json(&newer.to_json()),
+            Err(BackupError::TooNew { .. })
+        ));
+
+        let mut older = backup();
+        older.version = 0;
+        assert!(matches!(
+            Backup::from_json(&older.to_json()),
+            Err(BackupError::Unsupported(0))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_duplicate_notes() {
+        let mut backup = backup();
+        backup.notes.push(backup.notes[0].clone());
+
+        assert!(matches!(
+            Backup::from_json(&backup.to_json()),
+            Err(BackupError::DuplicateNote(id)) if id == "a"
+        ));
+    }
+}
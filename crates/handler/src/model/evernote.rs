@@ -0,0 +1,240 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use roxmltree::{Document, Node, ParsingOptions};
+use thiserror::Error;
+
+use super::import::{ImportedNote, titled_body};
+
+/// How an export dates its notes, e.g. `20250327T093000Z`.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Elements that end a line of text.
+const BLOCKS: &[&str] = &[
+    "div",
+    "p",
+    "li",
+    "ul",
+    "ol",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "tr",
+    "table",
+    "blockquote",
+    "pre",
+];
+
+#[derive(Debug, Error)]
+pub enum EnexError {
+    #[error("the file is not an Evernote export")]
+    NotAnExport,
+    #[error("the export is malformed: {0}")]
+    Malformed(#[from] roxmltree::Error),
+}
+
+/// Reads the notes of an Evernote export (`.enex`), each with its title.
+/// Attachments are left out.
+pub fn from_enex(text: &str) -> Result<Vec<(String, ImportedNote)>, EnexError> {
+    // Exports declare their DTD, but never define entities in it.
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(text, options)?;
+    let export = document.root_element();
+    if !export.has_tag_name("en-export") {
+        return Err(EnexError::NotAnExport);
+    }
+
+    let notes = export
+        .children()
+        .filter(|node| node.has_tag_name("note"))
+        .map(|note| {
+            let title = child_text(note, "title").unwrap_or_default().trim();
+            let content = child_text(note, "content")
+                .map(enml_to_text)
+                .unwrap_or_default();
+            let created_at = child_text(note, "created").and_then(timestamp);
+            let updated_at = child_text(note, "updated")
+                .and_then(timestamp)
+                .or(created_at);
+            let note = ImportedNote {
+                body: titled_body(title, &content),
+                created_at,
+                updated_at,
+                ..ImportedNote::default()
+            };
+            (title.to_string(), note)
+        })
+        .collect();
+    Ok(notes)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+}
+
+fn timestamp(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text.trim(), TIMESTAMP_FORMAT)
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+/// Turns the XHTML body of an Evernote note into plain text. Blocks and line
+/// breaks start new lines, list items and checkboxes become Markdown, and the
+/// rest of the markup is dropped.
+fn enml_to_text(enml: &str) -> String {
+    let mut text = String::new();
+    let mut rest = enml;
+    while let Some(start) = rest.find('<') {
+        push_text(&mut text, &rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "br" => text.push('\n'),
+            "hr" => new_line(&mut text),
+            "li" if !closing => {
+                new_line(&mut text);
+                text.push_str("- ");
+            }
+            "en-todo" if tag.contains("checked=\"true\"") => text.push_str("- [x] "),
+            "en-todo" => text.push_str("- [ ] "),
+            name if closing && BLOCKS.contains(&name) => new_line(&mut text),
+            _ => {}
+        }
+    }
+    push_text(&mut text, rest);
+
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn new_line(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+/// Appends the text between tags, folding whitespace as a browser would.
+fn push_text(text: &mut String, raw: &str) {
+    for c in decode_entities(raw).chars() {
+        if c == '\u{a0}' {
+            text.push(' ');
+        } else if !c.is_whitespace() {
+            text.push(c);
+        } else if !text.is_empty() && !text.ends_with([' ', '\n']) {
+            text.push(' ');
+        }
+    }
+}
+
+fn decode_entities(raw: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let entity = rest
+            .find(';')
+            .and_then(|end| Some((end, entity(&rest[..end])?)));
+        match entity {
+            Some((end, c)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = match name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_notes_from_an_export() {
+        let enex = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export export-date="20250327T093000Z" application="Evernote">
+  <note>
+    <title>Groceries</title>
+    <created>20250327T093000Z</created>
+    <updated>20250328T101500Z</updated>
+    <content><![CDATA[<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note>
+  <div><en-todo checked="true"/>milk &amp; eggs</div>
+  <div><en-todo checked="false"/>bread</div>
+  <div><br/></div>
+  <div>ask&nbsp;Sam&#x2026;</div>
+</en-note>]]></content>
+  </note>
+  <note>
+    <title></title>
+    <content><![CDATA[<en-note><ul><li>one</li><li>two</li></ul></en-note>]]></content>
+  </note>
+</en-export>"#;
+
+        let notes = from_enex(enex).unwrap();
+
+        assert_eq!(notes.len(), 2);
+        let (title, note) = &notes[0];
+        assert_eq!(title, "Groceries");
+        assert_eq!(
+            note.body,
+            "Groceries\n\n- [x] milk & eggs\n- [ ] bread\n\nask Sam…"
+        );
+        assert_eq!(note.created_at, timestamp("20250327T093000Z"));
+        assert_eq!(note.updated_at, timestamp("20250328T101500Z"));
+        assert_eq!(notes[1].1.body, "- one\n- two");
+        assert_eq!(notes[1].1.created_at, None);
+    }
+
+    #[test]
+    fn it_rejects_other_files() {
+        assert!(matches!(from_enex("<notes/>"), Err(EnexError::NotAnExport)));
+        assert!(matches!(
+            from_enex("<en-export>"),
+            Err(EnexError::Malformed(_))
+        ));
+    }
+}
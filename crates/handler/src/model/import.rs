@@ -0,0 +1,128 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Utc};
+
+/// A note read from outside the database. Whatever is missing is filled in
+/// as for a new note.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportedNote {
+    /// Kept when set to a note id, so that exporting and importing again
+    /// updates the same notes instead of duplicating them. Anything else is
+    /// replaced with a new id.
+    pub id: Option<String>,
+    pub body: String,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub location_x: Option<f32>,
+    pub location_y: Option<f32>,
+    /// A new note comes in hidden when this is not set.
+    pub is_active: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// What the source had that Sticky has nowhere to keep, such as "the
+    /// color yellow", to be reported.
+    pub left_out: Vec<String>,
+}
+
+impl ImportedNote {
+    /// Lays the imported fields over `note`.
+    pub(crate) fn apply_to(self, note: kernel::model::note::Note) -> kernel::model::note::Note {
+        kernel::model::note::Note {
+            id: note.id,
+            body: self.body,
+            width: self.width.unwrap_or(note.width),
+            height: self.height.unwrap_or(note.height),
+            location_x: self.location_x.unwrap_or(note.location_x),
+            location_y: self.location_y.unwrap_or(note.location_y),
+            is_active: self.is_active.unwrap_or(note.is_active),
+            created_at: self.created_at.unwrap_or(note.created_at),
+            updated_at: self.updated_at.unwrap_or(note.updated_at),
+            closed_at: self.closed_at,
+            deleted_at: self.deleted_at,
+        }
+    }
+}
+
+/// Puts the title of a note from another app on the first line of its body,
+/// where Sticky reads titles from.
+pub(crate) fn titled_body(title: &str, text: &str) -> String {
+    match (title.is_empty(), text.is_empty()) {
+        (true, _) => text.to_string(),
+        (false, true) => title.to_string(),
+        (false, false) => format!("{title}\n\n{text}"),
+    }
+}
+
+/// What to do with an imported note whose id is already taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    /// Keep the note in the database.
+    #[default]
+    Skip,
+    /// Overwrite the note in the database.
+    Replace,
+    /// Import the note under a new id.
+    KeepBoth,
+}
+
+impl FromStr for OnConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "replace" => Ok(Self::Replace),
+            "keep-both" => Ok(Self::KeepBoth),
+            _ => Err(format!(
+                "unknown conflict policy `{s}`, expected skip, replace or keep-both"
+            )),
+        }
+    }
+}
+
+/// The outcome of an import, note by note.
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    /// Ids of the notes that were added.
+    pub created: Vec<String>,
+    /// Ids of the notes that were overwritten.
+    pub replaced: Vec<String>,
+    pub skipped: Vec<Skipped>,
+    /// Parts of imported notes that were dropped.
+    pub left_out: Vec<LeftOut>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skipped {
+    /// Where the note came from, such as a file name.
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeftOut {
+    /// Where the note came from, such as a file name.
+    pub source: String,
+    pub what: String,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "imported {}, replaced {}, skipped {}",
+            self.created.len(),
+            self.replaced.len(),
+            self.skipped.len()
+        )?;
+        for skipped in &self.skipped {
+            write!(f, "\n  {}: {}", skipped.source, skipped.reason)?;
+        }
+        for left_out in &self.left_out {
+            write!(f, "\n  {}: left out {}", left_out.source, left_out.what)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::DateTime;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::import::{ImportedNote, titled_body};
+
+/// A note as Google Takeout exports it from Keep, one JSON file per note.
+/// Labels and attachments are left out; so are colors, but those are
+/// reported.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepNote {
+    #[serde(default)]
+    title: String,
+    color: Option<String>,
+    text_content: Option<String>,
+    list_content: Option<Vec<KeepListItem>>,
+    #[serde(default)]
+    is_trashed: bool,
+    #[serde(default)]
+    is_archived: bool,
+    created_timestamp_usec: Option<i64>,
+    user_edited_timestamp_usec: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepListItem {
+    text: String,
+    #[serde(default)]
+    is_checked: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum KeepError {
+    #[error("the file is not a Google Keep note")]
+    NotAKeepNote,
+    #[error("the note is in the Google Keep trash")]
+    Trashed,
+    #[error("the note is malformed: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+/// Reads a Google Keep note. Checklists become Markdown task lists, and
+/// archived notes are marked closed.
+pub fn from_keep_json(text: &str) -> Result<ImportedNote, KeepError> {
+    let note: KeepNote = serde_json::from_str(text)?;
+    let content = match (note.list_content, note.text_content) {
+        (Some(items), _) => items
+            .iter()
+            .map(|item| {
+                let mark = if item.is_checked { 'x' } else { ' ' };
+                format!("- [{mark}] {}", item.text.trim())
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (None, Some(text)) => text,
+        (None, None) => return Err(KeepError::NotAKeepNote),
+    };
+    if note.is_trashed {
+        return Err(KeepError::Trashed);
+    }
+
+    let updated_at = note
+        .user_edited_timestamp_usec
+        .and_then(DateTime::from_timestamp_micros);
+    let created_at = note
+        .created_timestamp_usec
+        .and_then(DateTime::from_timestamp_micros)
+        .or(updated_at);
+    // Keep calls its notes without a color `DEFAULT`.
+    let left_out = note
+        .color
+        .filter(|color| color != "DEFAULT")
+        .map(|color| format!("the color {}", color.to_lowercase()))
+        .into_iter()
+        .collect();
+    Ok(ImportedNote {
+        body: titled_body(note.title.trim(), content.trim()),
+        is_active: note.is_archived.then_some(false),
+        created_at,
+        updated_at,
+        closed_at: updated_at.filter(|_| note.is_archived),
+        left_out,
+        ..ImportedNote::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_text_and_list_notes() {
+        let text = from_keep_json(
+            r#"{
+                "color": "YELLOW",
+                "isTrashed": false,
+                "isPinned": true,
+                "isArchived": true,
+                "textContent": "buy milk",
+                "title": "Errands",
+                "userEditedTimestampUsec": 1743067800000000,
+                "createdTimestampUsec": 1743064200000000
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(text.body, "Errands\n\nbuy milk");
+        assert_eq!(text.is_active, Some(false));
+        assert_eq!(
+            text.created_at,
+            DateTime::from_timestamp_micros(1_743_064_200_000_000)
+        );
+        assert_eq!(text.closed_at, text.updated_at);
+        assert_eq!(text.left_out, vec!["the color yellow"]);
+
+        let list = from_keep_json(
+            r#"{
+                "title": "",
+                "textContent": "",
+                "listContent": [
+                    {"text": "milk", "isChecked": true},
+                    {"text": "eggs", "isChecked": false}
+                ],
+                "userEditedTimestampUsec": 1743067800000000
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(list.body, "- [x] milk\n- [ ] eggs");
+        assert_eq!(list.is_active, None);
+        assert_eq!(list.created_at, list.updated_at);
+        assert!(list.left_out.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_trashed_notes_and_other_files() {
+        assert!(matches!(
+            from_keep_json(r#"{"textContent": "old", "isTrashed": true}"#),
+            Err(KeepError::Trashed)
+        ));
+        assert!(matches!(
+            from_keep_json(r#"{"labels": []}"#),
+            Err(KeepError::NotAKeepNote)
+        ));
+        assert!(matches!(from_keep_json("[]"), Err(KeepError::Malformed(_))));
+    }
+}
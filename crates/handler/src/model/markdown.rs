@@ -0,0 +1,229 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use thiserror::Error;
+
+use super::{import::ImportedNote, note::Note};
+
+const DELIMITER: &str = "---";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FrontMatterError {
+    #[error("the front matter is not closed with `---`")]
+    Unterminated,
+    #[error("`{0}` is not a `key: value` line")]
+    InvalidLine(String),
+    #[error("`{value}` is not a valid {key}")]
+    InvalidValue { key: String, value: String },
+}
+
+/// Writes `note` as Markdown whose YAML front matter holds everything but
+/// the body:
+///
+/// ```text
+/// ---
+/// id: 01JQ6W3E0T8Z3Y5B7C9D1F2G4H
+/// width: 200
+/// height: 200
+/// location_x: 120
+/// location_y: 80
+/// is_active: true
+/// created_at: 2025-03-27T09:30:00.000Z
+/// updated_at: 2025-03-27T09:31:12.345Z
+/// ---
+/// buy milk
+/// ```
+pub fn to_markdown(note: &Note) -> String {
+    let mut markdown = format!(
+        "{DELIMITER}\n\
+         id: {}\n\
+         width: {}\n\
+         height: {}\n\
+         location_x: {}\n\
+         location_y: {}\n\
+         is_active: {}\n\
+         created_at: {}\n\
+         updated_at: {}\n",
+        note.id,
+        note.width,
+        note.height,
+        note.location_x,
+        note.location_y,
+        note.is_active,
+        timestamp(note.created_at),
+        timestamp(note.updated_at),
+    );
+    if let Some(closed_at) = note.closed_at {
+        markdown.push_str(&format!("closed_at: {}\n", timestamp(closed_at)));
+    }
+    if let Some(deleted_at) = note.deleted_at {
+        markdown.push_str(&format!("deleted_at: {}\n", timestamp(deleted_at)));
+    }
+    markdown.push_str(DELIMITER);
+    markdown.push('\n');
+    markdown.push_str(&note.body);
+    markdown
+}
+
+/// Reads a note written by [`to_markdown`]. Text without front matter is
+/// all body, and unknown keys are ignored.
+pub fn from_markdown(text: &str) -> Result<ImportedNote, FrontMatterError> {
+    let mut lines = text.split_inclusive('\n');
+    if lines.next().map(trim_line) != Some(DELIMITER) {
+        return Ok(ImportedNote {
+            body: text.to_string(),
+            ..ImportedNote::default()
+        });
+    }
+
+    let mut note = ImportedNote::default();
+    let mut read = text.find('\n').map_or(text.len(), |end| end + 1);
+    for line in lines {
+        read += line.len();
+        let line = trim_line(line);
+        if line == DELIMITER {
+            note.body = text[read..].to_string();
+            return Ok(note);
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| FrontMatterError::InvalidLine(line.to_string()))?;
+        set(&mut note, key.trim(), unquote(value.trim()))?;
+    }
+    Err(FrontMatterError::Unterminated)
+}
+
+fn set(note: &mut ImportedNote, key: &str, value: &str) -> Result<(), FrontMatterError> {
+    let invalid = || FrontMatterError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let number = || {
+        value
+            .parse::<f32>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .ok_or_else(invalid)
+    };
+    let time = || {
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|_| invalid())
+    };
+    let optional_time = || match value {
+        "" | "~" | "null" => Ok(None),
+        _ => time().map(Some),
+    };
+
+    match key {
+        "id" if !value.is_empty() => note.id = Some(value.to_string()),
+        "id" => return Err(invalid()),
+        "width" => note.width = Some(number()?),
+        "height" => note.height = Some(number()?),
+        "location_x" => note.location_x = Some(number()?),
+        "location_y" => note.location_y = Some(number()?),
+        "is_active" => note.is_active = Some(value.parse().map_err(|_| invalid())?),
+        "created_at" => note.created_at = Some(time()?),
+        "updated_at" => note.updated_at = Some(time()?),
+        "closed_at" => note.closed_at = optional_time()?,
+        "deleted_at" => note.deleted_at = optional_time()?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn trim_line(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(value)
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(body: &str) -> Note {
+        let created_at = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        Note {
+            id: "01JQ6W3E0T8Z3Y5B7C9D1F2G4H".into(),
+            body: body.into(),
+            width: 250.5,
+            height: 200.,
+            location_x: -40.,
+            location_y: 80.,
+            is_active: false,
+            created_at,
+            updated_at: created_at,
+            closed_at: Some(created_at),
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn it_round_trips_notes() {
+        let note = note("---\nbuy milk\n\n");
+
+        let imported = from_markdown(&to_markdown(&note)).unwrap();
+
+        assert_eq!(
+            imported,
+            ImportedNote {
+                id: Some(note.id),
+                body: note.body,
+                width: Some(note.width),
+                height: Some(note.height),
+                location_x: Some(note.location_x),
+                location_y: Some(note.location_y),
+                is_active: Some(note.is_active),
+                created_at: Some(note.created_at),
+                updated_at: Some(note.updated_at),
+                closed_at: note.closed_at,
+                deleted_at: None,
+                left_out: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn it_reads_text_without_front_matter_as_the_body() {
+        let imported = from_markdown("# groceries\n- milk\n").unwrap();
+
+        assert_eq!(imported.body, "# groceries\n- milk\n");
+        assert_eq!(imported.id, None);
+    }
+
+    #[test]
+    fn it_reads_hand_written_front_matter() {
+        let imported =
+            from_markdown("---\r\nid: \"abc\"\r\n# a comment\r\ncolor: yellow\r\n---\r\nmilk")
+                .unwrap();
+
+        assert_eq!(imported.id.as_deref(), Some("abc"));
+        assert_eq!(imported.body, "milk");
+    }
+
+    #[test]
+    fn it_rejects_broken_front_matter() {
+        assert_eq!(
+            from_markdown("---\nid: abc\n"),
+            Err(FrontMatterError::Unterminated)
+        );
+        assert_eq!(
+            from_markdown("---\nwidth: wide\n---\n"),
+            Err(FrontMatterError::InvalidValue {
+                key: "width".into(),
+                value: "wide".into()
+            })
+        );
+    }
+}
@@ -1 +1,9 @@
+pub mod backup;
+pub mod diff;
+pub mod evernote;
+pub mod import;
+pub mod keep;
+pub mod markdown;
 pub mod note;
+pub mod revision;
+pub mod search_result;
@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc};
+
 #[derive(Clone, Debug)]
 pub struct Note {
     pub id: String,
@@ -7,26 +9,29 @@ pub struct Note {
     pub location_x: f32,
     pub location_y: f32,
     pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
+const TITLE_MAX_CHARS: usize = 40;
+
 impl Note {
-    pub fn new(
-        id: String,
-        body: String,
-        width: f32,
-        height: f32,
-        location_x: f32,
-        location_y: f32,
-        is_active: bool,
-    ) -> Self {
-        Self {
-            id,
-            body,
-            width,
-            height,
-            location_x,
-            location_y,
-            is_active,
+    /// The first non-blank line of the body, shortened for use in lists.
+    pub fn title(&self) -> String {
+        let line = self
+            .body
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("Untitled");
+
+        if line.chars().count() > TITLE_MAX_CHARS {
+            let title: String = line.chars().take(TITLE_MAX_CHARS).collect();
+            format!("{title}…")
+        } else {
+            line.to_string()
         }
     }
 }
@@ -41,6 +46,44 @@ impl From<kernel::model::note::Note> for Note {
             location_x: note.location_x,
             location_y: note.location_y,
             is_active: note.is_active,
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+            closed_at: note.closed_at,
+            deleted_at: note.deleted_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(body: &str) -> Note {
+        Note {
+            id: "id".into(),
+            body: body.into(),
+            width: 200.,
+            height: 200.,
+            location_x: 0.,
+            location_y: 0.,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            closed_at: None,
+            deleted_at: None,
         }
     }
+
+    #[test]
+    fn it_uses_the_first_non_blank_line_as_title() {
+        assert_eq!(note("\n  \n  groceries \nmilk").title(), "groceries");
+        assert_eq!(note("").title(), "Untitled");
+    }
+
+    #[test]
+    fn it_shortens_long_titles() {
+        let title = note(&"a".repeat(50)).title();
+
+        assert_eq!(title, format!("{}…", "a".repeat(40)));
+    }
 }
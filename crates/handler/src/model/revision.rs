@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Clone, Debug)]
+pub struct Revision {
+    pub id: String,
+    pub note_id: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<kernel::model::revision::NoteRevision> for Revision {
+    fn from(revision: kernel::model::revision::NoteRevision) -> Self {
+        Self {
+            id: revision.id,
+            note_id: revision.note_id,
+            body: revision.body,
+            created_at: revision.created_at,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::ops::Range;
+
+use kernel::model::note::{NoteSearchResult, SNIPPET_MATCH_END, SNIPPET_MATCH_START};
+
+use super::note::Note;
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub note: Note,
+    /// Snippet text with the match markers stripped.
+    pub snippet: String,
+    /// Byte ranges of `snippet` that matched the query.
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl From<NoteSearchResult> for SearchResult {
+    fn from(result: NoteSearchResult) -> Self {
+        let mut snippet = String::with_capacity(result.snippet.len());
+        let mut highlights = vec![];
+        let mut start = None;
+
+        for c in result.snippet.chars() {
+            match c {
+                SNIPPET_MATCH_START => start = Some(snippet.len()),
+                SNIPPET_MATCH_END => {
+                    if let Some(start) = start.take() {
+                        highlights.push(start..snippet.len());
+                    }
+                }
+                c => snippet.push(c),
+            }
+        }
+
+        Self {
+            note: Note::from(result.note),
+            snippet,
+            highlights,
+        }
+    }
+}
@@ -1,62 +1,891 @@
-use std::rc::Rc;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
 
+use chrono::{DateTime, Utc};
+use clock::{Clock, SystemClock};
 use kernel::{
-    model::note::{UpdateNoteActiveEvent, UpdateNoteBodyEvent, UpdateNoteBoundsEvent},
+    error::{KernelError, Result},
+    model::{
+        note::{NoteSortKey, UpdateNoteActiveEvent, UpdateNoteBodyEvent, UpdateNoteBoundsEvent},
+        revision::NoteRevision,
+    },
     repository::note_repository::NoteRepository,
 };
+use ulid::Ulid;
+
+use crate::model::{
+    backup::Backup,
+    diff::{DiffLine, diff_lines},
+    evernote::from_enex,
+    import::{ImportReport, ImportedNote, LeftOut, OnConflict, Skipped},
+    keep::from_keep_json,
+    markdown::{from_markdown, to_markdown},
+    note::Note,
+    revision::Revision,
+    search_result::SearchResult,
+};
 
-use crate::model::note::Note;
+/// Edits within this interval of the latest revision are folded into it.
+const REVISION_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Clone)]
 pub struct NoteHandler {
     repository: Rc<dyn NoteRepository>,
+    revision_interval: Duration,
+    clock: Rc<dyn Clock>,
 }
 
 impl NoteHandler {
     pub fn new(repository: Rc<dyn NoteRepository>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            revision_interval: REVISION_INTERVAL,
+            clock: Rc::new(SystemClock),
+        }
+    }
+
+    pub fn with_revision_interval(mut self, revision_interval: Duration) -> Self {
+        self.revision_interval = revision_interval;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Rc::new(clock);
+        self
+    }
+
+    /// The time on the handler's clock, for timestamps taken outside of it.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now_utc()
+    }
+
+    pub fn create_note(&self) -> Result<Note> {
+        let note = self.repository.create_note()?;
+        Ok(Note::from(note))
+    }
+
+    pub fn create_note_with_body(&self, body: &str) -> Result<Note> {
+        let transaction = self.repository.transaction()?;
+        let id = self.repository.create_note()?.id;
+        self.repository.update_note_body(UpdateNoteBodyEvent {
+            id: id.clone(),
+            body: body.to_string(),
+        })?;
+        let note = self
+            .get_by_id(&id)?
+            .ok_or_else(|| KernelError::note_not_found(&id))?;
+        transaction.commit()?;
+        Ok(note)
+    }
+
+    pub fn get_all(&self, sort: NoteSortKey) -> Result<Vec<Note>> {
+        let notes = self.repository.get_notes(sort)?;
+        Ok(notes.into_iter().map(Note::from).collect())
+    }
+
+    pub fn get_by_id(&self, id: &str) -> Result<Option<Note>> {
+        let note = self.repository.get_note_by_id(id)?;
+        Ok(note.map(Note::from))
+    }
+
+    pub fn search_notes(&self, query: &str) -> Result<Vec<SearchResult>> {
+        let results = self.repository.search_notes(query)?;
+        Ok(results.into_iter().map(SearchResult::from).collect())
+    }
+
+    pub fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Result<()> {
+        // Snapshot the body being replaced so that a destructive edit can be
+        // undone from the revision history.
+        let transaction = self.repository.transaction()?;
+        self.record_revision(&event.id, false)?;
+        self.repository.update_note_body(event)?;
+        transaction.commit()
+    }
+
+    pub fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Result<()> {
+        self.repository.update_note_bounds(event)?;
+        Ok(())
+    }
+
+    pub fn set_note_active(&self, id: &str, is_active: bool) -> Result<()> {
+        self.repository.update_note_active(UpdateNoteActiveEvent {
+            id: id.to_string(),
+            is_active,
+        })
+    }
+
+    /// Marks every hidden note active again and returns the notes that were
+    /// reactivated.
+    pub fn show_all_notes(&self) -> Result<Vec<Note>> {
+        let transaction = self.repository.transaction()?;
+        let notes = self
+            .get_all(NoteSortKey::default())?
+            .into_iter()
+            .filter(|note| !note.is_active)
+            .map(|note| {
+                self.set_note_active(&note.id, true)?;
+                Ok(Note {
+                    is_active: true,
+                    ..note
+                })
+            })
+            .collect::<Result<_>>()?;
+        transaction.commit()?;
+        Ok(notes)
+    }
+
+    pub fn toggle_note_active(&self, id: &str) -> Result<()> {
+        let note = self
+            .repository
+            .get_note_by_id(id)?
+            .ok_or_else(|| KernelError::note_not_found(id))?;
+        self.repository.update_note_active(UpdateNoteActiveEvent {
+            id: note.id,
+            is_active: !note.is_active,
+        })
+    }
+
+    /// Moves a note to the trash.
+    pub fn delete_note(&self, id: &str) -> Result<()> {
+        self.repository.delete_note_by_id(id)
+    }
+
+    pub fn get_trash(&self) -> Result<Vec<Note>> {
+        let notes = self.repository.get_trashed_notes()?;
+        Ok(notes.into_iter().map(Note::from).collect())
+    }
+
+    pub fn restore_note(&self, id: &str) -> Result<Note> {
+        self.repository.restore_note_by_id(id)?;
+        self.get_by_id(id)?
+            .ok_or_else(|| KernelError::note_not_found(id))
+    }
+
+    pub fn purge_note(&self, id: &str) -> Result<()> {
+        self.repository.purge_note_by_id(id)
+    }
+
+    /// Permanently removes notes that have been in the trash for longer
+    /// than `retention`.
+    pub fn purge_trash(&self, retention: Duration) -> Result<usize> {
+        let Some(deleted_before) = chrono::Duration::from_std(retention)
+            .ok()
+            .and_then(|retention| self.clock.now_utc().checked_sub_signed(retention))
+        else {
+            return Ok(0);
+        };
+        self.repository.purge_trashed_notes(deleted_before)
+    }
+
+    /// Snapshots the current body of a note, e.g. when its window closes.
+    pub fn snapshot_note(&self, id: &str) -> Result<()> {
+        self.record_revision(id, true)
     }
 
-    pub fn create_note(&self) -> Note {
-        let note = self.repository.create_note().unwrap();
-        Note::from(note)
+    pub fn get_revisions(&self, note_id: &str) -> Result<Vec<Revision>> {
+        let revisions = self.repository.get_revisions(note_id)?;
+        Ok(revisions.into_iter().map(Revision::from).collect())
     }
 
-    pub fn get_all(&self) -> Vec<Note> {
-        match self.repository.get_notes() {
-            Ok(notes) => notes.into_iter().map(Note::from).collect(),
-            Err(_) => vec![],
+    /// Replaces the body of a note with a revision, keeping the replaced
+    /// body as a revision of its own.
+    pub fn restore_revision(&self, revision_id: &str) -> Result<Note> {
+        let revision = self.get_revision(revision_id)?;
+        let transaction = self.repository.transaction()?;
+        self.snapshot_note(&revision.note_id)?;
+        self.repository.update_note_body(UpdateNoteBodyEvent {
+            id: revision.note_id.clone(),
+            body: revision.body,
+        })?;
+        let note = self
+            .get_by_id(&revision.note_id)?
+            .ok_or_else(|| KernelError::note_not_found(&revision.note_id))?;
+        transaction.commit()?;
+        Ok(note)
+    }
+
+    /// Diff from the `from` revision to the `to` revision.
+    pub fn diff_revisions(&self, from_id: &str, to_id: &str) -> Result<Vec<DiffLine>> {
+        let from = self.get_revision(from_id)?;
+        let to = self.get_revision(to_id)?;
+        Ok(diff_lines(&from.body, &to.body))
+    }
+
+    /// Diff from a revision to the current body of its note.
+    pub fn diff_with_current(&self, revision_id: &str) -> Result<Vec<DiffLine>> {
+        let revision = self.get_revision(revision_id)?;
+        let note = self
+            .get_by_id(&revision.note_id)?
+            .ok_or_else(|| KernelError::note_not_found(&revision.note_id))?;
+        Ok(diff_lines(&revision.body, &note.body))
+    }
+
+    /// See [`NoteRepository::data_version`].
+    pub fn data_version(&self) -> Result<i64> {
+        self.repository.data_version()
+    }
+
+    /// Writes every note, trashed ones included, to `dir` as `<id>.md` and
+    /// returns how many were written.
+    pub fn export_markdown(&self, dir: &Path) -> Result<usize> {
+        fs::create_dir_all(dir)?;
+        let mut notes = self.get_all(NoteSortKey::default())?;
+        notes.extend(self.get_trash()?);
+        for note in &notes {
+            fs::write(dir.join(markdown_file_name(&note.id)), to_markdown(note))?;
         }
+        Ok(notes.len())
     }
 
-    pub fn get_by_id(&self, id: &str) -> Option<Note> {
-        if let Ok(note) = self.repository.get_note_by_id(id) {
-            note.map(Note::from)
-        } else {
-            None
+    /// Imports the Markdown files in `dir`, such as an export. Files that
+    /// cannot be read are skipped.
+    pub fn import_markdown(&self, dir: &Path, on_conflict: OnConflict) -> Result<ImportReport> {
+        let mut notes = vec![];
+        let mut skipped = vec![];
+        for path in files_with_extensions(dir, &["md", "markdown"])? {
+            let source = path.display().to_string();
+            let note = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| from_markdown(&text).map_err(|err| err.to_string()));
+            match note {
+                Ok(note) => notes.push((source, note)),
+                Err(reason) => skipped.push(Skipped { source, reason }),
+            }
         }
+
+        let mut report = self.import_notes(notes, on_conflict)?;
+        report.skipped.splice(0..0, skipped);
+        Ok(report)
     }
 
-    pub fn update_note_body(&self, event: UpdateNoteBodyEvent) {
-        self.repository.update_note_body(event).unwrap();
+    /// Imports the text and Markdown files in `dir`, such as notes kept by
+    /// another app, as new notes dated by the files. Bodies are taken as
+    /// they are, front matter included.
+    pub fn import_text(&self, dir: &Path) -> Result<ImportReport> {
+        let mut notes = vec![];
+        let mut skipped = vec![];
+        for path in files_with_extensions(dir, &["txt", "text", "md", "markdown"])? {
+            let source = path.display().to_string();
+            match read_text_note(&path) {
+                Ok(note) => notes.push((source, note)),
+                Err(err) => skipped.push(Skipped {
+                    source,
+                    reason: err.to_string(),
+                }),
+            }
+        }
+        self.import_new_notes(notes, skipped)
     }
 
-    pub fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) {
-        self.repository.update_note_bounds(event).unwrap();
+    /// Imports the notes of the Evernote export at `path` as new notes.
+    pub fn import_enex(&self, path: &Path) -> Result<ImportReport> {
+        let notes = from_enex(&fs::read_to_string(path)?)
+            .map_err(|err| KernelError::Invalid(err.to_string()))?
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (title, note))| {
+                let source = if title.is_empty() {
+                    format!("{}: note {}", path.display(), ix + 1)
+                } else {
+                    format!("{}: {title}", path.display())
+                };
+                (source, note)
+            })
+            .collect();
+        self.import_new_notes(notes, vec![])
     }
 
-    pub fn toggle_note_active(&self, id: &str) {
-        if let Some(note) = self.repository.get_note_by_id(id).unwrap() {
-            self.repository
-                .update_note_active(UpdateNoteActiveEvent {
-                    id: note.id,
-                    is_active: !note.is_active,
-                })
-                .unwrap();
+    /// Imports the Google Keep notes in `dir`, the `Keep` directory of a
+    /// Takeout archive, as new notes. Trashed notes are skipped.
+    pub fn import_keep(&self, dir: &Path) -> Result<ImportReport> {
+        let mut notes = vec![];
+        let mut skipped = vec![];
+        for path in files_with_extensions(dir, &["json"])? {
+            let source = path.display().to_string();
+            let note = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| from_keep_json(&text).map_err(|err| err.to_string()));
+            match note {
+                Ok(note) => notes.push((source, note)),
+                Err(reason) => skipped.push(Skipped { source, reason }),
+            }
+        }
+        self.import_new_notes(notes, skipped)
+    }
+
+    /// Writes every note, trashed ones included, to a JSON backup at `path`
+    /// and returns how many were written.
+    pub fn backup_to(&self, path: &Path) -> Result<usize> {
+        let mut notes = self.get_all(NoteSortKey::default())?;
+        notes.extend(self.get_trash()?);
+        let count = notes.len();
+        let backup = Backup::new(
+            notes,
+            self.repository.schema_version()?,
+            self.clock.now_utc(),
+        );
+        fs::write(path, backup.to_json())?;
+        Ok(count)
+    }
+
+    /// Restores the notes of the JSON backup at `path`. Notes made since the
+    /// backup are kept.
+    pub fn restore_from(&self, path: &Path, on_conflict: OnConflict) -> Result<ImportReport> {
+        let backup = Backup::from_json(&fs::read_to_string(path)?)
+            .map_err(|err| KernelError::Invalid(err.to_string()))?;
+        let notes = backup
+            .notes
+            .into_iter()
+            .map(|note| (format!("note {}", note.id), ImportedNote::from(note)))
+            .collect();
+        self.import_notes(notes, on_conflict)
+    }
+
+    /// Imports `notes`, each paired with where it came from, all or nothing.
+    /// A replaced note keeps its old body as a revision. New notes are
+    /// hidden unless they say otherwise.
+    pub fn import_notes(
+        &self,
+        notes: Vec<(String, ImportedNote)>,
+        on_conflict: OnConflict,
+    ) -> Result<ImportReport> {
+        let transaction = self.repository.transaction()?;
+        let mut report = ImportReport::default();
+        for (source, mut note) in notes {
+            // Only ids of our own are kept; anything else, such as a path
+            // written into front matter, gets a new one.
+            note.id = note.id.filter(|id| Ulid::from_string(id).is_ok());
+            let left_out = std::mem::take(&mut note.left_out);
+            let existing = match &note.id {
+                Some(id) => self.repository.get_note_by_id(id)?,
+                None => None,
+            };
+            let base = match (existing, &note.id, on_conflict) {
+                (Some(existing), _, OnConflict::Skip) => {
+                    report.skipped.push(Skipped {
+                        source,
+                        reason: format!("note {} already exists", existing.id),
+                    });
+                    continue;
+                }
+                (Some(existing), _, OnConflict::Replace) => {
+                    self.record_revision(&existing.id, true)?;
+                    report.replaced.push(existing.id.clone());
+                    self.repository.save_note(&note.apply_to(existing))?;
+                    report
+                        .left_out
+                        .extend(left_out.into_iter().map(|what| LeftOut {
+                            source: source.clone(),
+                            what,
+                        }));
+                    continue;
+                }
+                (None, Some(id), _) => {
+                    kernel::model::note::Note::new(id.clone(), self.clock.now_utc())
+                }
+                // Kept both, or the note never had an id.
+                _ => self.repository.create_note()?,
+            };
+            // New notes come in hidden unless the source says otherwise, so
+            // that a large import does not open a window for every note.
+            let base = kernel::model::note::Note {
+                is_active: false,
+                ..base
+            };
+            let note = note.apply_to(base);
+            self.repository.save_note(&note)?;
+            report.created.push(note.id);
+            report
+                .left_out
+                .extend(left_out.into_iter().map(|what| LeftOut {
+                    source: source.clone(),
+                    what,
+                }));
         }
+        transaction.commit()?;
+        Ok(report)
+    }
+
+    /// Imports notes from another app, which have no ids to clash, after
+    /// skipping the empty ones. `skipped` lists what could not be read.
+    fn import_new_notes(
+        &self,
+        notes: Vec<(String, ImportedNote)>,
+        mut skipped: Vec<Skipped>,
+    ) -> Result<ImportReport> {
+        let (empty, notes): (Vec<_>, Vec<_>) = notes
+            .into_iter()
+            .partition(|(_, note)| note.body.trim().is_empty());
+        skipped.extend(empty.into_iter().map(|(source, _)| Skipped {
+            source,
+            reason: "the note is empty".to_string(),
+        }));
+
+        let mut report = self.import_notes(notes, OnConflict::Skip)?;
+        report.skipped.splice(0..0, skipped);
+        Ok(report)
+    }
+
+    fn get_revision(&self, id: &str) -> Result<NoteRevision> {
+        self.repository
+            .get_revision_by_id(id)?
+            .ok_or_else(|| KernelError::revision_not_found(id))
+    }
+
+    /// Snapshots the note unless it is empty, unchanged since the latest
+    /// revision, or (when not forced) that revision is still recent.
+    fn record_revision(&self, id: &str, force: bool) -> Result<()> {
+        let Some(note) = self.repository.get_note_by_id(id)? else {
+            return Ok(());
+        };
+        if note.body.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(latest) = self.repository.get_latest_revision(id)? {
+            let recent = self
+                .clock
+                .now_utc()
+                .signed_duration_since(latest.created_at)
+                .to_std()
+                .is_ok_and(|age| age < self.revision_interval);
+            if latest.body == note.body || (recent && !force) {
+                return Ok(());
+            }
+        }
+
+        self.repository.create_revision(id)?;
+        Ok(())
+    }
+}
+
+/// The file a note is exported to. Ids are checked on import, but the name
+/// is kept to plain characters all the same, so that no id can point the
+/// file outside the export directory.
+fn markdown_file_name(id: &str) -> String {
+    let name = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("{name}.md")
+}
+
+/// The files in `dir` with one of `extensions`, in name order.
+fn files_with_extensions(dir: &Path, extensions: &[&str]) -> io::Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extensions.contains(&extension))
+    });
+    paths.sort();
+    Ok(paths)
+}
+
+fn read_text_note(path: &Path) -> io::Result<ImportedNote> {
+    let body = fs::read_to_string(path)?;
+    let metadata = fs::metadata(path)?;
+    let updated_at = metadata.modified().ok().map(DateTime::<Utc>::from);
+    let created_at = metadata
+        .created()
+        .ok()
+        .map(DateTime::<Utc>::from)
+        .or(updated_at);
+    Ok(ImportedNote {
+        body: body.trim_end().to_string(),
+        created_at,
+        updated_at,
+        ..ImportedNote::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use adapter::repository_impl::note_repository_impl::NoteRepositoryImpl;
+    use chrono::DateTime;
+    use clock::ManualClock;
+    use db::{Db, utils::TempDir};
+
+    use super::*;
+
+    fn handler() -> (NoteHandler, ManualClock) {
+        let clock = ManualClock::at(DateTime::from_timestamp_millis(1_700_000_000_000).unwrap());
+        let repository =
+            NoteRepositoryImpl::new(Db::open_in_memory().unwrap().conn).with_clock(clock.clone());
+        let handler = NoteHandler::new(Rc::new(repository)).with_clock(clock.clone());
+        (handler, clock)
+    }
+
+    fn set_body(handler: &NoteHandler, id: &str, body: &str) {
+        handler
+            .update_note_body(UpdateNoteBodyEvent {
+                id: id.to_string(),
+                body: body.to_string(),
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn it_creates_an_empty_active_note() {
+        let (handler, _) = handler();
+
+        let note = handler.create_note().unwrap();
+
+        assert_eq!(note.body, "");
+        assert!(note.is_active);
+        let ids = handler
+            .get_all(NoteSortKey::default())
+            .unwrap()
+            .into_iter()
+            .map(|note| note.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![note.id]);
+    }
+
+    #[test]
+    fn it_creates_a_note_with_a_body() {
+        let (handler, _) = handler();
+
+        let note = handler.create_note_with_body("milk").unwrap();
+
+        assert_eq!(note.body, "milk");
+        assert!(handler.get_revisions(&note.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_updates_the_body() {
+        let (handler, _) = handler();
+        let id = handler.create_note().unwrap().id;
+
+        set_body(&handler, &id, "milk");
+
+        assert_eq!(handler.get_by_id(&id).unwrap().unwrap().body, "milk");
+    }
+
+    #[test]
+    fn it_toggles_a_note_active() {
+        let (handler, _) = handler();
+        let id = handler.create_note().unwrap().id;
+
+        handler.toggle_note_active(&id).unwrap();
+        let hidden = handler.get_by_id(&id).unwrap().unwrap();
+        handler.toggle_note_active(&id).unwrap();
+        let shown = handler.get_by_id(&id).unwrap().unwrap();
+
+        assert!(!hidden.is_active);
+        assert!(hidden.closed_at.is_some());
+        assert!(shown.is_active);
+    }
+
+    #[test]
+    fn it_fails_to_toggle_an_unknown_note() {
+        let (handler, _) = handler();
+
+        let result = handler.toggle_note_active("missing");
+
+        assert!(matches!(result, Err(KernelError::NotFound { .. })));
+    }
+
+    #[test]
+    fn it_shows_every_hidden_note() {
+        let (handler, _) = handler();
+        let shown = handler.create_note().unwrap().id;
+        let hidden = handler.create_note().unwrap().id;
+        handler.set_note_active(&hidden, false).unwrap();
+
+        let reactivated = handler.show_all_notes().unwrap();
+
+        let ids = reactivated
+            .into_iter()
+            .map(|note| note.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![hidden]);
+        assert!(
+            handler
+                .get_all(NoteSortKey::default())
+                .unwrap()
+                .iter()
+                .all(|note| note.is_active)
+        );
+        assert!(handler.get_by_id(&shown).unwrap().unwrap().is_active);
+    }
+
+    #[test]
+    fn it_moves_deleted_notes_to_the_trash_and_back() {
+        let (handler, _) = handler();
+        let id = handler.create_note().unwrap().id;
+
+        handler.delete_note(&id).unwrap();
+        assert!(handler.get_all(NoteSortKey::default()).unwrap().is_empty());
+        assert_eq!(handler.get_trash().unwrap().len(), 1);
+
+        let restored = handler.restore_note(&id).unwrap();
+        assert_eq!(restored.id, id);
+        assert!(restored.deleted_at.is_none());
+        assert!(handler.get_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_purges_only_notes_trashed_longer_than_the_retention() {
+        let (handler, clock) = handler();
+        let old = handler.create_note().unwrap().id;
+        let recent = handler.create_note().unwrap().id;
+        handler.delete_note(&old).unwrap();
+        clock.advance(Duration::from_secs(10 * 60));
+        handler.delete_note(&recent).unwrap();
+        clock.advance(Duration::from_secs(10 * 60));
+
+        let purged = handler.purge_trash(Duration::from_secs(15 * 60)).unwrap();
+
+        assert_eq!(purged, 1);
+        let trash = handler.get_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, recent);
+    }
+
+    #[test]
+    fn it_folds_quick_edits_into_one_revision() {
+        let (handler, clock) = handler();
+        let id = handler.create_note().unwrap().id;
+
+        set_body(&handler, &id, "one");
+        set_body(&handler, &id, "two");
+        clock.advance(Duration::from_secs(5));
+        set_body(&handler, &id, "three");
+        clock.advance(REVISION_INTERVAL);
+        set_body(&handler, &id, "four");
+
+        let bodies = handler
+            .get_revisions(&id)
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.body)
+            .collect::<Vec<_>>();
+        assert_eq!(bodies, vec!["three", "one"]);
+    }
+
+    #[test]
+    fn it_restores_a_revision_and_keeps_the_replaced_body() {
+        let (handler, clock) = handler();
+        let id = handler.create_note().unwrap().id;
+        set_body(&handler, &id, "draft");
+        handler.snapshot_note(&id).unwrap();
+        clock.advance(Duration::from_secs(5));
+        set_body(&handler, &id, "oops");
+        let draft = handler.get_revisions(&id).unwrap().remove(0);
+
+        let note = handler.restore_revision(&draft.id).unwrap();
+
+        assert_eq!(note.body, "draft");
+        let bodies = handler
+            .get_revisions(&id)
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.body)
+            .collect::<Vec<_>>();
+        assert_eq!(bodies, vec!["oops", "draft"]);
+    }
+
+    #[test]
+    fn it_round_trips_notes_through_markdown() {
+        let (handler, _) = handler();
+        let kept = handler.create_note_with_body("milk").unwrap();
+        let trashed = handler.create_note_with_body("eggs").unwrap();
+        handler.set_note_active(&kept.id, false).unwrap();
+        handler.delete_note(&trashed.id).unwrap();
+        let dir = TempDir::new();
+
+        assert_eq!(handler.export_markdown(dir.path()).unwrap(), 2);
+        let (other, _) = self::handler();
+        let report = other.import_markdown(dir.path(), OnConflict::Skip).unwrap();
+
+        assert_eq!(report.created.len(), 2);
+        let imported = other.get_by_id(&kept.id).unwrap().unwrap();
+        assert_eq!(imported.body, "milk");
+        assert!(!imported.is_active);
+        assert_eq!(
+            imported.closed_at,
+            handler.get_by_id(&kept.id).unwrap().unwrap().closed_at
+        );
+        assert_eq!(other.get_trash().unwrap()[0].id, trashed.id);
+    }
+
+    #[test]
+    fn it_resolves_import_conflicts() {
+        let (handler, _) = handler();
+        let id = handler.create_note_with_body("milk").unwrap().id;
+        let imported = || {
+            vec![(
+                "milk.md".to_string(),
+                ImportedNote {
+                    id: Some(id.clone()),
+                    body: "oat milk".to_string(),
+                    ..ImportedNote::default()
+                },
+            )]
+        };
+
+        let skipped = handler.import_notes(imported(), OnConflict::Skip).unwrap();
+        assert_eq!(skipped.skipped[0].source, "milk.md");
+        assert_eq!(handler.get_by_id(&id).unwrap().unwrap().body, "milk");
+
+        let both = handler
+            .import_notes(imported(), OnConflict::KeepBoth)
+            .unwrap();
+        assert_ne!(both.created[0], id);
+        assert_eq!(handler.get_all(NoteSortKey::default()).unwrap().len(), 2);
+
+        let replaced = handler
+            .import_notes(imported(), OnConflict::Replace)
+            .unwrap();
+        assert_eq!(replaced.replaced, vec![id.clone()]);
+        assert_eq!(handler.get_by_id(&id).unwrap().unwrap().body, "oat milk");
+        assert_eq!(handler.get_revisions(&id).unwrap()[0].body, "milk");
+    }
+
+    #[test]
+    fn it_imports_new_notes_hidden() {
+        let (handler, _) = handler();
+        let imported = |is_active| {
+            (
+                "milk.md".to_string(),
+                ImportedNote {
+                    body: "milk".to_string(),
+                    is_active,
+                    ..ImportedNote::default()
+                },
+            )
+        };
+
+        let report = handler
+            .import_notes(
+                vec![imported(None), imported(Some(false)), imported(Some(true))],
+                OnConflict::Skip,
+            )
+            .unwrap();
+
+        let shown = report
+            .created
+            .iter()
+            .map(|id| handler.get_by_id(id).unwrap().unwrap().is_active)
+            .collect::<Vec<_>>();
+        assert_eq!(shown, vec![false, false, true]);
     }
 
-    pub fn delete_note(&self, id: &str) {
-        self.repository.delete_note_by_id(id).unwrap();
+    #[test]
+    fn it_skips_markdown_files_it_cannot_read() {
+        let (handler, _) = handler();
+        let dir = TempDir::new();
+        fs::write(dir.join("a.md"), "---\nwidth: wide\n---\nmilk").unwrap();
+        fs::write(dir.join("b.md"), "eggs").unwrap();
+        fs::write(dir.join("c.txt"), "not a note").unwrap();
+
+        let report = handler
+            .import_markdown(dir.path(), OnConflict::Skip)
+            .unwrap();
+
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].source.ends_with("a.md"));
+    }
+
+    #[test]
+    fn it_gives_new_ids_to_imported_notes_with_foreign_ids() {
+        let (handler, _) = handler();
+        let dir = TempDir::new();
+        fs::write(dir.join("a.md"), "---\nid: ../../escaped\n---\nmilk").unwrap();
+
+        let report = handler
+            .import_markdown(dir.path(), OnConflict::Skip)
+            .unwrap();
+
+        let id = &report.created[0];
+        assert!(Ulid::from_string(id).is_ok());
+        let export = dir.join("export");
+        assert_eq!(handler.export_markdown(&export).unwrap(), 1);
+        assert!(export.join(format!("{id}.md")).exists());
+        assert_eq!(markdown_file_name("../x"), "___x.md");
+    }
+
+    #[test]
+    fn it_restores_a_backup() {
+        let (handler, _) = handler();
+        let id = handler.create_note_with_body("milk").unwrap().id;
+        let dir = TempDir::new();
+        let path = dir.join("backup.json");
+        handler.backup_to(&path).unwrap();
+        set_body(&handler, &id, "oat milk");
+        let later = handler.create_note_with_body("eggs").unwrap().id;
+
+        let report = handler.restore_from(&path, OnConflict::Replace).unwrap();
+
+        assert_eq!(report.replaced, vec![id.clone()]);
+        assert_eq!(handler.get_by_id(&id).unwrap().unwrap().body, "milk");
+        assert!(handler.get_by_id(&later).unwrap().is_some());
+        fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            handler.restore_from(&path, OnConflict::Replace),
+            Err(KernelError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn it_imports_notes_from_other_apps() {
+        let (handler, _) = handler();
+        let dir = TempDir::new();
+        fs::write(dir.join("milk.txt"), "buy milk\n").unwrap();
+        fs::write(dir.join("empty.md"), "\n").unwrap();
+        fs::write(
+            dir.join("keep.json"),
+            r#"{"title": "eggs", "textContent": "", "color": "BLUE"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("trashed.json"),
+            r#"{"textContent": "old", "isTrashed": true}"#,
+        )
+        .unwrap();
+        let enex = dir.join("notes.enex");
+        fs::write(
+            &enex,
+            "<en-export><note><title>bread</title><content/></note></en-export>",
+        )
+        .unwrap();
+
+        let text = handler.import_text(dir.path()).unwrap();
+        assert_eq!(text.created.len(), 1);
+        assert_eq!(text.skipped[0].reason, "the note is empty");
+        let keep = handler.import_keep(dir.path()).unwrap();
+        assert_eq!(keep.created.len(), 1);
+        assert!(keep.skipped[0].source.ends_with("trashed.json"));
+        assert!(keep.left_out[0].source.ends_with("keep.json"));
+        assert_eq!(keep.left_out[0].what, "the color blue");
+        let evernote = handler.import_enex(&enex).unwrap();
+        assert_eq!(evernote.created.len(), 1);
+
+        let mut bodies = handler
+            .get_all(NoteSortKey::default())
+            .unwrap()
+            .into_iter()
+            .map(|note| note.body)
+            .collect::<Vec<_>>();
+        bodies.sort();
+        assert_eq!(bodies, vec!["bread", "buy milk", "eggs"]);
+        assert!(matches!(
+            handler.import_enex(&dir.join("milk.txt")),
+            Err(KernelError::Invalid(_))
+        ));
     }
 }
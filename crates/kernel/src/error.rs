@@ -0,0 +1,45 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum KernelError {
+    #[error("{kind} {id} does not exist")]
+    NotFound { kind: &'static str, id: String },
+    /// The data could be read, but not understood.
+    #[error("the database is corrupt: {0}")]
+    Corrupt(String),
+    /// Another writer holds the database, or a change clashed with one.
+    #[error("the change conflicts with another one: {0}")]
+    Conflict(String),
+    /// The session was opened read-only after the database could not be
+    /// opened for writing.
+    #[error("the database is open read-only, so changes cannot be saved")]
+    ReadOnly,
+    /// A file outside the database, such as a backup, is not in a form that
+    /// can be read.
+    #[error("{0}")]
+    Invalid(String),
+    /// A file outside the database, such as an export, could not be read
+    /// or written.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("storage error: {0}")]
+    Storage(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl KernelError {
+    pub fn note_not_found(id: &str) -> Self {
+        Self::NotFound {
+            kind: "note",
+            id: id.to_string(),
+        }
+    }
+
+    pub fn revision_not_found(id: &str) -> Self {
+        Self::NotFound {
+            kind: "revision",
+            id: id.to_string(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, KernelError>;
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use gpui::{Bounds, Pixels};
 
 #[derive(Clone, Debug)]
@@ -9,6 +10,46 @@ pub struct Note {
     pub location_x: f32,
     pub location_y: f32,
     pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Width and height of a new note.
+pub const DEFAULT_NOTE_SIZE: f32 = 200.;
+/// Where a new note opens, on both axes.
+pub const DEFAULT_NOTE_LOCATION: f32 = 200.;
+
+impl Note {
+    /// An empty, active note at the default size and location.
+    pub fn new(id: String, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            body: String::new(),
+            width: DEFAULT_NOTE_SIZE,
+            height: DEFAULT_NOTE_SIZE,
+            location_x: DEFAULT_NOTE_LOCATION,
+            location_y: DEFAULT_NOTE_LOCATION,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            deleted_at: None,
+        }
+    }
+}
+
+/// Order of the notes returned by `NoteRepository::get_notes`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoteSortKey {
+    /// Newest first.
+    #[default]
+    Created,
+    /// Most recently edited first.
+    Updated,
+    /// Alphabetical by body.
+    Title,
 }
 
 #[derive(Clone, Debug)]
@@ -28,3 +69,15 @@ pub struct UpdateNoteActiveEvent {
     pub id: String,
     pub is_active: bool,
 }
+
+/// Marks the start of a matched term inside `NoteSearchResult::snippet`.
+pub const SNIPPET_MATCH_START: char = '\u{2}';
+/// Marks the end of a matched term inside `NoteSearchResult::snippet`.
+pub const SNIPPET_MATCH_END: char = '\u{3}';
+
+#[derive(Clone, Debug)]
+pub struct NoteSearchResult {
+    pub note: Note,
+    pub snippet: String,
+    pub rank: f64,
+}
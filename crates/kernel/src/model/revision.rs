@@ -0,0 +1,9 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Clone, Debug)]
+pub struct NoteRevision {
+    pub id: String,
+    pub note_id: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -1 +1,2 @@
 pub mod note_repository;
+pub mod transaction;
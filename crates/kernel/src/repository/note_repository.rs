@@ -1,13 +1,57 @@
-use std::error::Error;
+use chrono::{DateTime, Utc};
 
-use crate::model::note::{Note, UpdateNoteActiveEvent, UpdateNoteBodyEvent, UpdateNoteBoundsEvent};
+use super::transaction::Transaction;
+use crate::{
+    error::Result,
+    model::{
+        note::{
+            Note, NoteSearchResult, NoteSortKey, UpdateNoteActiveEvent, UpdateNoteBodyEvent,
+            UpdateNoteBoundsEvent,
+        },
+        revision::NoteRevision,
+    },
+};
 
+/// Updates, deletes and restores of a single note fail with
+/// [`KernelError::NotFound`](crate::error::KernelError::NotFound) when no
+/// note has the given id.
 pub trait NoteRepository {
-    fn get_notes(&self) -> Result<Vec<Note>, Box<dyn Error>>;
-    fn get_note_by_id(&self, id: &str) -> Result<Option<Note>, Box<dyn Error>>;
-    fn create_note(&self) -> Result<Note, Box<dyn Error>>;
-    fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Result<String, Box<dyn Error>>;
-    fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Result<String, Box<dyn Error>>;
-    fn update_note_active(&self, event: UpdateNoteActiveEvent) -> Result<(), Box<dyn Error>>;
-    fn delete_note_by_id(&self, id: &str) -> Result<(), Box<dyn Error>>;
+    /// Starts a unit of work covering every call made on this repository
+    /// until it is committed or dropped.
+    fn transaction(&self) -> Result<Box<dyn Transaction + '_>>;
+    fn get_notes(&self, sort: NoteSortKey) -> Result<Vec<Note>>;
+    fn get_note_by_id(&self, id: &str) -> Result<Option<Note>>;
+    fn search_notes(&self, query: &str) -> Result<Vec<NoteSearchResult>>;
+    fn create_note(&self) -> Result<Note>;
+    /// Writes `note` as it is, timestamps included, replacing the note with
+    /// the same id if there is one.
+    fn save_note(&self, note: &Note) -> Result<()>;
+    /// Fails with `NotFound` for a note in the trash, so that a late write
+    /// from a closed editor cannot change it.
+    fn update_note_body(&self, event: UpdateNoteBodyEvent) -> Result<String>;
+    /// Fails with `NotFound` for a note in the trash, as `update_note_body`.
+    fn update_note_bounds(&self, event: UpdateNoteBoundsEvent) -> Result<String>;
+    fn update_note_active(&self, event: UpdateNoteActiveEvent) -> Result<()>;
+    /// Moves a note to the trash.
+    fn delete_note_by_id(&self, id: &str) -> Result<()>;
+    fn get_trashed_notes(&self) -> Result<Vec<Note>>;
+    /// Takes a note out of the trash; fails if it is not in the trash.
+    fn restore_note_by_id(&self, id: &str) -> Result<()>;
+    /// Permanently removes a trashed note.
+    fn purge_note_by_id(&self, id: &str) -> Result<()>;
+    /// Permanently removes notes trashed before `deleted_before` and returns
+    /// how many were removed.
+    fn purge_trashed_notes(&self, deleted_before: DateTime<Utc>) -> Result<usize>;
+    /// Snapshots the current body of a note.
+    fn create_revision(&self, note_id: &str) -> Result<NoteRevision>;
+    /// Revisions of a note, newest first.
+    fn get_revisions(&self, note_id: &str) -> Result<Vec<NoteRevision>>;
+    fn get_revision_by_id(&self, id: &str) -> Result<Option<NoteRevision>>;
+    fn get_latest_revision(&self, note_id: &str) -> Result<Option<NoteRevision>>;
+    /// Changes whenever someone else, such as another process, commits a
+    /// change to the notes. Changes made through this repository leave it
+    /// as it is.
+    fn data_version(&self) -> Result<i64>;
+    /// The version of the storage schema, recorded in backups.
+    fn schema_version(&self) -> Result<u32>;
 }
@@ -0,0 +1,9 @@
+use crate::error::Result;
+
+/// A unit of work. Everything written through the repository that opened it
+/// is kept only if it is committed; dropping it without committing rolls
+/// those writes back. Transactions can be nested, in which case only the
+/// outermost commit makes the writes durable.
+pub trait Transaction {
+    fn commit(self: Box<Self>) -> Result<()>;
+}
@@ -1,5 +1,10 @@
 use gpui::{App, Menu, MenuItem};
 use gpui_component::input::{Copy, Cut, Paste, Redo, Undo};
+use window::{
+    backup::{BackUpNotes, RestoreBackup, RestoreSnapshot},
+    editor::{ReopenLastClosed, ShowAllNotes},
+    list::OpenNoteList,
+};
 
 pub fn init(cx: &mut App) {
     cx.set_menus(vec![
@@ -18,5 +23,18 @@ pub fn init(cx: &mut App) {
                 MenuItem::os_action("Paste", Paste, gpui::OsAction::Paste),
             ],
         },
+        Menu {
+            name: "Notes".into(),
+            items: vec![
+                MenuItem::action("Show Note List", OpenNoteList),
+                MenuItem::separator(),
+                MenuItem::action("Show All Notes", ShowAllNotes),
+                MenuItem::action("Reopen Closed Note", ReopenLastClosed),
+                MenuItem::separator(),
+                MenuItem::action("Restore from Backup…", RestoreSnapshot),
+                MenuItem::action("Back Up Notes to File…", BackUpNotes),
+                MenuItem::action("Restore Notes from File…", RestoreBackup),
+            ],
+        },
     ]);
 }
@@ -0,0 +1,105 @@
+use std::{env, path::PathBuf, time::Duration};
+
+use gpui::Global;
+
+/// Environment variable that overrides how long trashed notes are kept.
+pub const TRASH_RETENTION_ENV: &str = "STICKY_TRASH_RETENTION_DAYS";
+/// Environment variable that overrides how often the database is
+/// snapshotted.
+pub const BACKUP_INTERVAL_ENV: &str = "STICKY_BACKUP_INTERVAL_MINUTES";
+/// Environment variable that overrides how many snapshots are kept.
+pub const BACKUP_GENERATIONS_ENV: &str = "STICKY_BACKUP_GENERATIONS";
+
+const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+const DEFAULT_BACKUP_INTERVAL_MINUTES: u64 = 15;
+const DEFAULT_BACKUP_GENERATIONS: usize = 10;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const SECONDS_PER_MINUTE: u64 = 60;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub db_path: PathBuf,
+    /// Keep the notes in memory instead of `db_path`, so nothing outlives
+    /// the session.
+    pub ephemeral: bool,
+    /// The database was opened read-only after it could not be opened for
+    /// writing.
+    pub read_only: bool,
+    /// Trashed notes older than this are purged at startup.
+    pub trash_retention: Duration,
+    /// How often the database is snapshotted while it changes, besides at
+    /// startup.
+    pub backup_interval: Duration,
+    /// How many snapshots are kept; none are taken if this is 0.
+    pub backup_generations: usize,
+}
+
+impl Config {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self {
+            db_path,
+            ephemeral: false,
+            read_only: false,
+            trash_retention: resolve_trash_retention(None),
+            backup_interval: resolve_backup_interval(None),
+            backup_generations: resolve_backup_generations(None),
+        }
+    }
+
+    pub fn with_trash_retention(mut self, trash_retention: Duration) -> Self {
+        self.trash_retention = trash_retention;
+        self
+    }
+
+    pub fn with_ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn with_backup_interval(mut self, backup_interval: Duration) -> Self {
+        self.backup_interval = backup_interval;
+        self
+    }
+
+    pub fn with_backup_generations(mut self, backup_generations: usize) -> Self {
+        self.backup_generations = backup_generations;
+        self
+    }
+}
+
+impl Global for Config {}
+
+/// Resolves the trash retention from an explicit number of days (the
+/// `--trash-retention-days` flag), then `$STICKY_TRASH_RETENTION_DAYS`, then
+/// the 30 day default.
+pub fn resolve_trash_retention(explicit_days: Option<u64>) -> Duration {
+    let days = explicit_days
+        .or_else(|| env::var(TRASH_RETENTION_ENV).ok()?.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+    Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY))
+}
+
+/// Resolves the snapshot interval from an explicit number of minutes (the
+/// `--backup-interval-minutes` flag), then `$STICKY_BACKUP_INTERVAL_MINUTES`,
+/// then the 15 minute default. It is never shorter than a minute.
+pub fn resolve_backup_interval(explicit_minutes: Option<u64>) -> Duration {
+    let minutes = explicit_minutes
+        .or_else(|| env::var(BACKUP_INTERVAL_ENV).ok()?.parse().ok())
+        .unwrap_or(DEFAULT_BACKUP_INTERVAL_MINUTES)
+        .max(1);
+    Duration::from_secs(minutes.saturating_mul(SECONDS_PER_MINUTE))
+}
+
+/// Resolves how many snapshots are kept from an explicit number (the
+/// `--backup-generations` flag), then `$STICKY_BACKUP_GENERATIONS`, then the
+/// default of 10.
+pub fn resolve_backup_generations(explicit: Option<usize>) -> usize {
+    explicit
+        .or_else(|| env::var(BACKUP_GENERATIONS_ENV).ok()?.parse().ok())
+        .unwrap_or(DEFAULT_BACKUP_GENERATIONS)
+}
@@ -1,23 +1,26 @@
-use std::rc::Rc;
+use std::{io, rc::Rc};
 
 use adapter::repository_impl::note_repository_impl::NoteRepositoryImpl;
 use gpui::Global;
-use handler::note_handler::NoteHandler;
+use handler::{async_note_handler::AsyncNoteHandler, note_handler::NoteHandler};
 use rusqlite::Connection;
 
 #[derive(Clone)]
 pub struct AppHandler {
-    pub note_handler: NoteHandler,
+    pub note_handler: AsyncNoteHandler,
 }
 
 impl AppHandler {
-    pub fn new(conn: Connection) -> Self {
-        let note_handler = NoteHandler::new(Rc::new(NoteRepositoryImpl::new(conn)));
+    /// Moves `conn` to the database thread.
+    pub fn new(conn: Connection) -> io::Result<Self> {
+        let note_handler = AsyncNoteHandler::spawn(move || {
+            NoteHandler::new(Rc::new(NoteRepositoryImpl::new(conn)))
+        })?;
 
-        Self { note_handler }
+        Ok(Self { note_handler })
     }
 
-    pub fn note_handler(&self) -> NoteHandler {
+    pub fn note_handler(&self) -> AsyncNoteHandler {
         self.note_handler.clone()
     }
 }
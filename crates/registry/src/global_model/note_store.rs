@@ -5,6 +5,8 @@ pub use handler::model::note::Note;
 pub struct NoteStore {
     pub notes: Vec<Note>,
     pub new_notes: Vec<Note>,
+    /// Ids of notes closed during this session, most recent last.
+    pub closed_note_ids: Vec<String>,
 }
 
 impl Global for NoteStore {}
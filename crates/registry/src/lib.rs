@@ -1,37 +1,138 @@
+pub mod config;
 pub mod global_model;
 
-use db::Db;
+use std::path::PathBuf;
+
+use config::Config;
+use db::{Db, recovery, snapshot::Snapshots};
+use futures::executor::block_on;
 use global_model::{app_handler::AppHandler, note_store::NoteStore};
 use gpui::App;
 use handler::model::note::Note;
+use kernel::model::note::NoteSortKey;
 
-pub fn init(cx: &mut App) {
-    init_handler(cx);
+/// Opens the database and sets up the globals. Nothing is set up if the
+/// database cannot be opened; call `init` again to retry, or recover with
+/// [`start_fresh`] or [`open_read_only`].
+pub fn init(cx: &mut App, config: &Config) -> db::error::Result<()> {
+    let db = if config.ephemeral {
+        Db::open_in_memory()?
+    } else {
+        recovery::open_with_retry(&config.db_path)?
+    };
+    init_handler(cx, db, config)?;
     init_store(cx);
+    init_snapshots(cx, config);
+    cx.set_global(config.clone());
+    Ok(())
 }
 
-fn init_handler(cx: &mut App) {
-    let db = match Db::new() {
-        Ok(client) => {
-            client.prepare_database().unwrap();
-            client
-        }
-        Err(e) => panic!("Failed to connect to database: {e}"),
-    };
-    cx.set_global(AppHandler::new(db.conn));
+/// Moves a broken database out of the way and starts over with an empty
+/// one. Returns where the broken database went.
+pub fn start_fresh(cx: &mut App, config: &Config) -> db::error::Result<PathBuf> {
+    let moved_to = recovery::move_aside(&config.db_path)?;
+    init(cx, config)?;
+    Ok(moved_to)
+}
+
+/// Opens the database without writing to it, so that notes can at least be
+/// read. Every change fails with `KernelError::ReadOnly`.
+pub fn open_read_only(cx: &mut App, config: &Config) -> db::error::Result<()> {
+    let db = Db::open_read_only(&config.db_path)?;
+    cx.set_global(AppHandler::new(db.conn)?);
+    init_store(cx);
+    cx.set_global(config.clone().with_read_only(true));
+    Ok(())
+}
+
+fn init_handler(cx: &mut App, db: Db, config: &Config) -> db::error::Result<()> {
+    let app_handler = AppHandler::new(db.conn)?;
+    let purged = app_handler
+        .note_handler()
+        .purge_trash(config.trash_retention);
+    cx.background_executor()
+        .spawn(async move {
+            if let Err(e) = purged.await {
+                eprintln!("Failed to purge the trash: {e}");
+            }
+        })
+        .detach();
+    cx.set_global(app_handler);
+    Ok(())
+}
+
+/// Snapshots the database now, and again every `backup_interval` while it
+/// keeps changing.
+fn init_snapshots(cx: &mut App, config: &Config) {
+    if config.ephemeral || config.backup_generations == 0 {
+        return;
+    }
+    let executor = cx.background_executor().clone();
+    let note_handler = cx.global::<AppHandler>().note_handler();
+    let db_path = config.db_path.clone();
+    let (generations, interval) = (config.backup_generations, config.backup_interval);
+    cx.background_executor()
+        .spawn(async move {
+            let mut snapshots = match Snapshots::open(&db_path, generations) {
+                Ok(snapshots) => snapshots,
+                Err(e) => {
+                    eprintln!("Failed to start backups: {e}");
+                    return;
+                }
+            };
+            loop {
+                // Stamped by the clock the notes themselves are stamped by.
+                match note_handler.now().await {
+                    Ok(now) => {
+                        if let Err(e) = snapshots.take(now) {
+                            eprintln!("Failed to back up the database: {e}");
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to back up the database: {e}"),
+                }
+                executor.timer(interval).await;
+            }
+        })
+        .detach();
 }
 
 pub fn init_store(cx: &mut App) {
-    let notes = cx.global::<AppHandler>().note_handler.get_all();
+    // The only wait on the database thread: there is nothing to render
+    // before the notes are loaded.
+    let notes = block_on(
+        cx.global::<AppHandler>()
+            .note_handler
+            .get_all(NoteSortKey::default()),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to load notes: {e}");
+        vec![]
+    });
 
     let note_store = NoteStore {
         notes,
         new_notes: vec![],
+        closed_note_ids: vec![],
     };
 
     cx.set_global(note_store);
 }
 
+/// Replaces the known notes after they were changed by another process.
+pub fn set_notes(cx: &mut App, notes: Vec<Note>) {
+    cx.global_mut::<NoteStore>().notes = notes;
+}
+
 pub fn add_note(cx: &mut App, note: Note) {
     cx.global_mut::<NoteStore>().new_notes.push(note);
 }
+
+pub fn push_closed_note(cx: &mut App, id: &str) {
+    cx.global_mut::<NoteStore>()
+        .closed_note_ids
+        .push(id.to_string());
+}
+
+pub fn pop_closed_note(cx: &mut App) -> Option<String> {
+    cx.global_mut::<NoteStore>().closed_note_ids.pop()
+}
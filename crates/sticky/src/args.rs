@@ -0,0 +1,156 @@
+use std::{path::PathBuf, str::FromStr};
+
+#[derive(Default)]
+pub struct Args {
+    pub db: Option<PathBuf>,
+    pub trash_retention_days: Option<u64>,
+    pub backup_interval_minutes: Option<u64>,
+    pub backup_generations: Option<usize>,
+    pub ephemeral: bool,
+    /// A control request to send to the running instance instead of
+    /// starting one.
+    pub send: Option<String>,
+    /// Print the output of a command as JSON.
+    pub json: bool,
+    /// What `import` does with notes that already exist.
+    pub on_conflict: Option<String>,
+    /// The words that are not flags, naming a command to run instead of
+    /// starting the app.
+    pub command: Vec<String>,
+    /// Flags of ours given without a value or with one that does not parse.
+    /// Nothing runs while there are any.
+    pub invalid: Vec<String>,
+    /// Flags that are not ours. Commands refuse them, but the app ignores
+    /// them, since platforms may pass their own flags to GUI applications.
+    pub unknown: Vec<String>,
+}
+
+impl Args {
+    /// Parses `--db <path>`, `--trash-retention-days <days>`,
+    /// `--backup-interval-minutes <minutes>`, `--backup-generations <count>`,
+    /// `--send <request>` and `--on-conflict <policy>`, also accepted as
+    /// `--flag=value`, `--ephemeral` and `--json`. Other words are collected
+    /// as the command, and so is everything after `--`, so that text can
+    /// start with `-`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            match flag {
+                "--" => parsed.command.extend(args.by_ref()),
+                "--ephemeral" if inline.is_none() => parsed.ephemeral = true,
+                "--json" if inline.is_none() => parsed.json = true,
+                "--db"
+                | "--trash-retention-days"
+                | "--backup-interval-minutes"
+                | "--backup-generations"
+                | "--send"
+                | "--on-conflict" => {
+                    // A flag right after is not taken for a missing value.
+                    let value = inline.or_else(|| args.next_if(|next| !is_flag(next)));
+                    let set = match value {
+                        Some(value) => parsed.set(flag, value),
+                        None => Err(format!("`{flag}` needs a value")),
+                    };
+                    if let Err(message) = set {
+                        parsed.invalid.push(message);
+                    }
+                }
+                _ if is_flag(&arg) => parsed.unknown.push(arg),
+                _ => parsed.command.push(arg),
+            }
+        }
+
+        parsed
+    }
+
+    fn set(&mut self, flag: &str, value: String) -> Result<(), String> {
+        match flag {
+            "--db" => self.db = Some(PathBuf::from(value)),
+            "--trash-retention-days" => self.trash_retention_days = Some(number(flag, &value)?),
+            "--backup-interval-minutes" => {
+                self.backup_interval_minutes = Some(number(flag, &value)?);
+            }
+            "--backup-generations" => self.backup_generations = Some(number(flag, &value)?),
+            "--send" => self.send = Some(value),
+            "--on-conflict" => self.on_conflict = Some(value),
+            _ => unreachable!("`{flag}` takes no value"),
+        }
+        Ok(())
+    }
+}
+
+fn number<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a valid number for `{flag}`"))
+}
+
+/// Whether `arg` looks like a flag, as opposed to text such as `- milk` or
+/// `-5`.
+fn is_flag(arg: &str) -> bool {
+    let mut chars = arg.chars();
+    chars.next() == Some('-') && chars.next().is_some_and(|c| c == '-' || c.is_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn it_parses_flags_in_both_forms() {
+        let args = parse(&["--db", "notes.sqlite", "--trash-retention-days=7", "--json"]);
+
+        assert_eq!(args.db, Some(PathBuf::from("notes.sqlite")));
+        assert_eq!(args.trash_retention_days, Some(7));
+        assert!(args.json);
+        assert!(args.command.is_empty());
+    }
+
+    #[test]
+    fn it_keeps_text_that_starts_with_a_dash() {
+        assert_eq!(parse(&["new", "- buy milk"]).command, ["new", "- buy milk"]);
+        assert_eq!(
+            parse(&["new", "--json", "--", "call", "--mom"]).command,
+            ["new", "call", "--mom"]
+        );
+        assert!(
+            parse(&["new", "--", "x", "--json"])
+                .command
+                .contains(&"--json".to_string())
+        );
+    }
+
+    #[test]
+    fn it_reports_flags_without_a_valid_value() {
+        let args = parse(&["--db", "--json", "--trash-retention-days=soon"]);
+
+        assert_eq!(args.db, None);
+        assert!(args.json);
+        assert_eq!(
+            args.invalid,
+            [
+                "`--db` needs a value",
+                "`soon` is not a valid number for `--trash-retention-days`"
+            ]
+        );
+        assert_eq!(parse(&["list", "--db"]).invalid, ["`--db` needs a value"]);
+    }
+
+    #[test]
+    fn it_collects_unknown_flags() {
+        let args = parse(&["search", "-foo", "-psn_0_42"]);
+
+        assert_eq!(args.command, ["search"]);
+        assert_eq!(args.unknown, ["-foo", "-psn_0_42"]);
+    }
+}
@@ -0,0 +1,479 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
+};
+
+use adapter::repository_impl::note_repository_impl::NoteRepositoryImpl;
+use chrono::{DateTime, SecondsFormat, Utc};
+use db::{Db, error::DbError, recovery};
+use handler::{
+    model::{
+        import::{ImportReport, OnConflict},
+        note::Note,
+        search_result::SearchResult,
+    },
+    note_handler::NoteHandler,
+};
+use kernel::{error::KernelError, model::note::NoteSortKey};
+use registry::config::Config;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::args::Args;
+
+const USAGE: &str = "usage: sticky [list | new <text> | show <id> | cat <id> | rm <id> | search <query> | export <dir> | import <dir> | import-text <dir> | import-evernote <file> | import-keep <dir> | backup <file> | restore <file>] [--on-conflict skip|replace|keep-both] [--json]";
+
+/// A command that works on the database directly, without starting the app.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    List,
+    New {
+        body: String,
+    },
+    /// Prints a note with its details.
+    Show {
+        id: String,
+    },
+    /// Prints only the body of a note.
+    Cat {
+        id: String,
+    },
+    /// Moves a note to the trash.
+    Rm {
+        id: String,
+    },
+    Search {
+        query: String,
+    },
+    /// Writes every note to a directory of Markdown files.
+    Export {
+        dir: PathBuf,
+    },
+    /// Reads notes back from a directory of Markdown files.
+    Import {
+        dir: PathBuf,
+        on_conflict: OnConflict,
+    },
+    /// Reads new notes from a directory of text or Markdown files kept by
+    /// another app.
+    ImportText {
+        dir: PathBuf,
+    },
+    /// Reads new notes from an Evernote export.
+    ImportEvernote {
+        file: PathBuf,
+    },
+    /// Reads new notes from the Google Keep directory of a Takeout archive.
+    ImportKeep {
+        dir: PathBuf,
+    },
+    /// Writes every note to a JSON backup.
+    Backup {
+        file: PathBuf,
+    },
+    /// Puts back the notes of a JSON backup.
+    Restore {
+        file: PathBuf,
+        on_conflict: OnConflict,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("{USAGE}")]
+    Usage,
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error(transparent)]
+    Db(#[from] DbError),
+    #[error(transparent)]
+    Kernel(#[from] KernelError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Command {
+    /// Parses the words left over after the flags, e.g. `["new", "buy",
+    /// "milk"]`, and the flags that go with them.
+    pub fn parse(args: &Args) -> Result<Self, CliError> {
+        if let Some(flag) = args.unknown.first() {
+            return Err(CliError::InvalidArgument(format!(
+                "unknown flag `{flag}`; put `--` before text that starts with `-`"
+            )));
+        }
+        let Some((name, rest)) = args.command.split_first() else {
+            return Err(CliError::Usage);
+        };
+        let text = || {
+            (!rest.is_empty())
+                .then(|| rest.join(" "))
+                .ok_or(CliError::Usage)
+        };
+        let id = || match rest {
+            [id] => Ok(id.clone()),
+            _ => Err(CliError::Usage),
+        };
+        let on_conflict = |default| {
+            args.on_conflict
+                .as_deref()
+                .map_or(Ok(default), str::parse)
+                .map_err(CliError::InvalidArgument)
+        };
+
+        let command = match name.as_str() {
+            "list" if rest.is_empty() => Self::List,
+            "new" => Self::New { body: text()? },
+            "show" => Self::Show { id: id()? },
+            "cat" => Self::Cat { id: id()? },
+            "rm" => Self::Rm { id: id()? },
+            "search" => Self::Search { query: text()? },
+            "export" => Self::Export { dir: id()?.into() },
+            "import" => Self::Import {
+                dir: id()?.into(),
+                on_conflict: on_conflict(OnConflict::Skip)?,
+            },
+            "import-text" => Self::ImportText { dir: id()?.into() },
+            "import-evernote" => Self::ImportEvernote { file: id()?.into() },
+            "import-keep" => Self::ImportKeep { dir: id()?.into() },
+            "backup" => Self::Backup { file: id()?.into() },
+            // A backup is restored to bring notes back, so it wins by default.
+            "restore" => Self::Restore {
+                file: id()?.into(),
+                on_conflict: on_conflict(OnConflict::Replace)?,
+            },
+            _ => return Err(CliError::Usage),
+        };
+        Ok(command)
+    }
+}
+
+/// Runs the command named by `args` on the database of `config` and prints
+/// the result to stdout.
+pub fn execute(config: &Config, args: &Args) -> ExitCode {
+    let result = Command::parse(args).and_then(|command| {
+        let handler = open(config)?;
+        let mut out = io::stdout().lock();
+        run(&handler, command, args.json, &mut out)
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("sticky: {err}");
+            match err {
+                CliError::Usage | CliError::InvalidArgument(_) => ExitCode::from(2),
+                _ => ExitCode::FAILURE,
+            }
+        }
+    }
+}
+
+fn open(config: &Config) -> Result<NoteHandler, CliError> {
+    let db = if config.ephemeral {
+        Db::open_in_memory()?
+    } else {
+        recovery::open_with_retry(&config.db_path)?
+    };
+    Ok(NoteHandler::new(Rc::new(NoteRepositoryImpl::new(db.conn))))
+}
+
+pub fn run(
+    handler: &NoteHandler,
+    command: Command,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    match command {
+        Command::List => {
+            let notes = handler.get_all(NoteSortKey::default())?;
+            if json {
+                return print_json(out, notes.iter().map(note_json).collect());
+            }
+            for note in &notes {
+                let shown = if note.is_active { "shown" } else { "hidden" };
+                writeln!(out, "{}  {shown:<6}  {}", note.id, note.title())?;
+            }
+        }
+        Command::New { body } => {
+            let note = handler.create_note_with_body(&body)?;
+            if json {
+                return print_json(out, note_json(&note));
+            }
+            writeln!(out, "{}", note.id)?;
+        }
+        Command::Show { id } => {
+            let note = get(handler, &id)?;
+            if json {
+                return print_json(out, note_json(&note));
+            }
+            writeln!(out, "id:      {}", note.id)?;
+            writeln!(
+                out,
+                "shown:   {}",
+                if note.is_active { "yes" } else { "no" }
+            )?;
+            writeln!(out, "created: {}", timestamp(note.created_at))?;
+            writeln!(out, "updated: {}", timestamp(note.updated_at))?;
+            if let Some(deleted_at) = note.deleted_at {
+                writeln!(out, "trashed: {}", timestamp(deleted_at))?;
+            }
+            writeln!(out)?;
+            writeln!(out, "{}", note.body)?;
+        }
+        Command::Cat { id } => {
+            let note = get(handler, &id)?;
+            if json {
+                return print_json(out, note_json(&note));
+            }
+            write!(out, "{}", note.body)?;
+            if !note.body.ends_with('\n') {
+                writeln!(out)?;
+            }
+        }
+        Command::Rm { id } => {
+            handler.delete_note(&id)?;
+            if json {
+                return print_json(out, note_json(&get(handler, &id)?));
+            }
+        }
+        Command::Search { query } => {
+            let results = handler.search_notes(&query)?;
+            if json {
+                return print_json(out, results.iter().map(search_result_json).collect());
+            }
+            for result in &results {
+                let snippet = result.snippet.split_whitespace().collect::<Vec<_>>();
+                writeln!(out, "{}  {}", result.note.id, snippet.join(" "))?;
+            }
+        }
+        Command::Export { dir } => {
+            let count = handler.export_markdown(&dir)?;
+            if json {
+                return print_json(out, json!({ "exported": count }));
+            }
+            writeln!(out, "exported {count} notes to {}", dir.display())?;
+        }
+        Command::Import { dir, on_conflict } => {
+            print_report(out, &handler.import_markdown(&dir, on_conflict)?, json)?;
+        }
+        Command::ImportText { dir } => {
+            print_report(out, &handler.import_text(&dir)?, json)?;
+        }
+        Command::ImportEvernote { file } => {
+            print_report(out, &handler.import_enex(&file)?, json)?;
+        }
+        Command::ImportKeep { dir } => {
+            print_report(out, &handler.import_keep(&dir)?, json)?;
+        }
+        Command::Backup { file } => {
+            let count = handler.backup_to(&file)?;
+            if json {
+                return print_json(out, json!({ "backed_up": count }));
+            }
+            writeln!(out, "backed up {count} notes to {}", file.display())?;
+        }
+        Command::Restore { file, on_conflict } => {
+            print_report(out, &handler.restore_from(&file, on_conflict)?, json)?;
+        }
+    }
+    Ok(())
+}
+
+fn get(handler: &NoteHandler, id: &str) -> Result<Note, CliError> {
+    handler
+        .get_by_id(id)?
+        .ok_or_else(|| KernelError::note_not_found(id).into())
+}
+
+fn print_json(out: &mut impl Write, value: Value) -> Result<(), CliError> {
+    serde_json::to_writer_pretty(&mut *out, &value).map_err(io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_report(out: &mut impl Write, report: &ImportReport, json: bool) -> Result<(), CliError> {
+    if json {
+        return print_json(out, report_json(report));
+    }
+    writeln!(out, "{report}")?;
+    Ok(())
+}
+
+fn note_json(note: &Note) -> Value {
+    json!({
+        "id": note.id,
+        "body": note.body,
+        "is_active": note.is_active,
+        "width": note.width,
+        "height": note.height,
+        "location_x": note.location_x,
+        "location_y": note.location_y,
+        "created_at": timestamp(note.created_at),
+        "updated_at": timestamp(note.updated_at),
+        "closed_at": note.closed_at.map(timestamp),
+        "deleted_at": note.deleted_at.map(timestamp),
+    })
+}
+
+fn search_result_json(result: &SearchResult) -> Value {
+    json!({
+        "note": note_json(&result.note),
+        "snippet": result.snippet,
+    })
+}
+
+fn report_json(report: &ImportReport) -> Value {
+    json!({
+        "created": report.created,
+        "replaced": report.replaced,
+        "skipped": report
+            .skipped
+            .iter()
+            .map(|skipped| json!({ "source": skipped.source, "reason": skipped.reason }))
+            .collect::<Vec<_>>(),
+        "left_out": report
+            .left_out
+            .iter()
+            .map(|left_out| json!({ "source": left_out.source, "what": left_out.what }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        Command::parse(&Args::parse(args.iter().map(|arg| arg.to_string())))
+    }
+
+    fn handler() -> NoteHandler {
+        let db = Db::open_in_memory().unwrap();
+        NoteHandler::new(Rc::new(NoteRepositoryImpl::new(db.conn)))
+    }
+
+    fn output(handler: &NoteHandler, command: Command, json: bool) -> String {
+        let mut out = vec![];
+        run(handler, command, json, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_parses_commands() {
+        assert_eq!(
+            parse(&["new", "buy", "milk"]).unwrap(),
+            Command::New {
+                body: "buy milk".to_string()
+            }
+        );
+        assert_eq!(
+            parse(&["rm", "a"]).unwrap(),
+            Command::Rm {
+                id: "a".to_string()
+            }
+        );
+        assert!(parse(&["rm", "a", "b"]).is_err());
+        assert!(parse(&["new"]).is_err());
+        assert!(parse(&["explode"]).is_err());
+        assert!(matches!(
+            parse(&["new", "call", "--mom"]),
+            Err(CliError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            parse(&["new", "--", "call", "--mom"]).unwrap(),
+            Command::New {
+                body: "call --mom".to_string()
+            }
+        );
+        assert_eq!(
+            parse(&["import", "notes", "--on-conflict=keep-both"]).unwrap(),
+            Command::Import {
+                dir: "notes".into(),
+                on_conflict: OnConflict::KeepBoth
+            }
+        );
+        assert_eq!(
+            parse(&["restore", "notes.json"]).unwrap(),
+            Command::Restore {
+                file: "notes.json".into(),
+                on_conflict: OnConflict::Replace
+            }
+        );
+        assert_eq!(
+            parse(&["import-keep", "Takeout/Keep"]).unwrap(),
+            Command::ImportKeep {
+                dir: "Takeout/Keep".into()
+            }
+        );
+        assert!(matches!(
+            parse(&["import", "notes", "--on-conflict", "merge"]),
+            Err(CliError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn it_creates_and_prints_notes() {
+        let handler = handler();
+
+        let id = output(
+            &handler,
+            Command::New {
+                body: "buy milk".into(),
+            },
+            false,
+        );
+        let id = id.trim().to_string();
+
+        assert_eq!(
+            output(&handler, Command::Cat { id: id.clone() }, false),
+            "buy milk\n"
+        );
+        assert!(
+            output(&handler, Command::List, false).contains(&format!("{id}  shown   buy milk"))
+        );
+    }
+
+    #[test]
+    fn it_prints_json() {
+        let handler = handler();
+        handler.create_note_with_body("buy milk").unwrap();
+
+        let notes: Value = serde_json::from_str(&output(&handler, Command::List, true)).unwrap();
+
+        assert_eq!(notes[0]["body"], "buy milk");
+        assert_eq!(notes[0]["is_active"], true);
+        assert_eq!(notes[0]["deleted_at"], Value::Null);
+    }
+
+    #[test]
+    fn it_moves_notes_to_the_trash() {
+        let handler = handler();
+        let note = handler.create_note_with_body("buy milk").unwrap();
+
+        output(
+            &handler,
+            Command::Rm {
+                id: note.id.clone(),
+            },
+            false,
+        );
+
+        assert!(handler.get_all(NoteSortKey::default()).unwrap().is_empty());
+        assert!(matches!(
+            run(
+                &handler,
+                Command::Rm {
+                    id: "missing".into()
+                },
+                false,
+                &mut vec![]
+            ),
+            Err(CliError::Kernel(KernelError::NotFound { .. }))
+        ));
+    }
+}
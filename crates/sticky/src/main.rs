@@ -1,13 +1,88 @@
+mod args;
+mod cli;
+
+use std::process::ExitCode;
+
+use args::Args;
 use gpui::{App, Application};
+use registry::config::{
+    Config, resolve_backup_generations, resolve_backup_interval, resolve_trash_retention,
+};
+use window::recovery::RecoveryWindow;
+
+fn main() -> ExitCode {
+    let args = Args::parse(std::env::args().skip(1));
+    if !args.invalid.is_empty() {
+        for message in &args.invalid {
+            eprintln!("sticky: {message}");
+        }
+        return ExitCode::from(2);
+    }
+    let Some(db_path) = db::path::resolve_database_path(args.db.clone()) else {
+        eprintln!(
+            "sticky: there is no data or home directory for the notes; pass --db <path> or set {}",
+            db::path::DATABASE_PATH_ENV
+        );
+        return ExitCode::FAILURE;
+    };
+    let config = Config::new(db_path)
+        .with_trash_retention(resolve_trash_retention(args.trash_retention_days))
+        .with_ephemeral(args.ephemeral)
+        .with_backup_interval(resolve_backup_interval(args.backup_interval_minutes))
+        .with_backup_generations(resolve_backup_generations(args.backup_generations));
+
+    if !args.command.is_empty() {
+        return cli::execute(&config, &args);
+    }
+
+    #[cfg(unix)]
+    if !config.ephemeral {
+        let socket = control::socket_path(&config.db_path);
+        if let Some(request) = &args.send {
+            return send(&socket, request);
+        }
+        // One instance runs per database; launching another one shows the
+        // notes of the running instance instead.
+        if control::client::send(&socket, &control::Request::ShowAll).is_ok() {
+            return ExitCode::SUCCESS;
+        }
+    }
 
-fn main() {
     Application::new().run(|cx: &mut App| {
         gpui_component::init(cx);
         theme::init(cx);
         menu::init(cx);
-        registry::init(cx);
-        window::init(cx);
+        match registry::init(cx, &config) {
+            Ok(()) => window::init(cx),
+            Err(err) => RecoveryWindow::open(cx, config, err),
+        }
 
         cx.activate(true);
     });
+    ExitCode::SUCCESS
+}
+
+/// Sends a JSON control request to the running instance and prints its
+/// response.
+#[cfg(unix)]
+fn send(socket: &std::path::Path, request: &str) -> ExitCode {
+    let response = serde_json::from_str(request)
+        .map_err(|err| format!("invalid request: {err}"))
+        .and_then(|request| {
+            control::client::send(socket, &request)
+                .map_err(|err| format!("no running instance answered: {err}"))
+        });
+    match response {
+        Ok(response) => {
+            println!("{}", serde_json::to_string(&response).unwrap_or_default());
+            match response {
+                control::Response::Ok { .. } => ExitCode::SUCCESS,
+                control::Response::Error { .. } => ExitCode::FAILURE,
+            }
+        }
+        Err(message) => {
+            eprintln!("sticky: {message}");
+            ExitCode::FAILURE
+        }
+    }
 }
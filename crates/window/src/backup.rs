@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use gpui::{App, PathPromptOptions, actions};
+use handler::model::import::OnConflict;
+use registry::{config::Config, global_model::app_handler::AppHandler};
+
+use crate::{
+    notification::{notify, notify_error},
+    snapshots::SnapshotWindow,
+    watcher::reload,
+};
+
+actions!(backup, [BackUpNotes, RestoreBackup, RestoreSnapshot]);
+
+pub fn init(cx: &mut App) {
+    cx.on_action(back_up_notes);
+    // Restoring fails in a read-only session. In an ephemeral one, restoring
+    // a snapshot would overwrite the database on disk, so restores are left
+    // out there too. Without a handler, the menu items are disabled.
+    let config = cx.global::<Config>();
+    if config.read_only || config.ephemeral {
+        return;
+    }
+    cx.on_action(restore_backup);
+    cx.on_action(|_: &RestoreSnapshot, cx| SnapshotWindow::open(cx));
+}
+
+fn back_up_notes(_: &BackUpNotes, cx: &mut App) {
+    let note_handler = cx.global::<AppHandler>().note_handler();
+    let path = cx.prompt_for_new_path(&default_directory());
+    cx.spawn(async move |cx| {
+        let Ok(Ok(Some(path))) = path.await else {
+            return;
+        };
+        let backed_up = note_handler.backup_to(&path).await;
+        cx.update(|cx| match backed_up {
+            Ok(count) => notify(
+                cx,
+                format!("Backed up {count} notes to {}.", path.display()),
+            ),
+            Err(err) => notify_error(cx, &err),
+        })
+        .ok();
+    })
+    .detach();
+}
+
+/// Puts back the notes of a backup, overwriting the ones that changed
+/// since. Notes made since the backup are kept.
+fn restore_backup(_: &RestoreBackup, cx: &mut App) {
+    let note_handler = cx.global::<AppHandler>().note_handler();
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+    });
+    cx.spawn(async move |cx| {
+        let Ok(Ok(Some(paths))) = paths.await else {
+            return;
+        };
+        let Some(path) = paths.into_iter().next() else {
+            return;
+        };
+        match note_handler.restore_from(&path, OnConflict::Replace).await {
+            Ok(report) => {
+                reload(cx, &note_handler).await;
+                let message = format!("Restored {}: {report}", path.display());
+                cx.update(|cx| notify(cx, message)).ok();
+            }
+            Err(err) => {
+                cx.update(|cx| notify_error(cx, &err)).ok();
+            }
+        }
+    })
+    .detach();
+}
+
+fn default_directory() -> PathBuf {
+    dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default()
+}
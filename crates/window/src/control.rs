@@ -0,0 +1,153 @@
+use std::{fs, path::PathBuf};
+
+use control::{NoteInfo, Request, Response, server};
+use futures::StreamExt;
+use gpui::{App, AsyncApp};
+use kernel::{error::KernelError, model::note::NoteSortKey};
+use registry::{
+    add_note,
+    global_model::{app_handler::AppHandler, note_store::Note},
+    push_closed_note,
+};
+
+use crate::editor_delegate::EditorDelegate;
+
+/// Lets scripts and other sticky processes drive this instance through the
+/// control socket at `socket`.
+pub fn init(cx: &mut App, socket: PathBuf) {
+    let mut requests = match server::listen(&socket) {
+        Ok(requests) => requests,
+        Err(err) => {
+            eprintln!("sticky: cannot listen on {}: {err}", socket.display());
+            return;
+        }
+    };
+
+    cx.on_app_quit(move |_| {
+        fs::remove_file(&socket).ok();
+        async {}
+    })
+    .detach();
+
+    cx.spawn(async move |cx| {
+        while let Some((request, reply)) = requests.next().await {
+            let response = match handle(cx, request).await {
+                Ok(notes) => Response::Ok {
+                    notes: notes.into_iter().map(note_info).collect(),
+                },
+                Err(message) => Response::error(message),
+            };
+            reply.send(response).ok();
+        }
+    })
+    .detach();
+}
+
+/// Carries out `request` and returns the notes it touched.
+async fn handle(cx: &mut AsyncApp, request: Request) -> Result<Vec<Note>, String> {
+    let note_handler = cx
+        .update(|cx| cx.global::<AppHandler>().note_handler())
+        .map_err(|err| err.to_string())?;
+
+    match request {
+        Request::Create { body } => {
+            let note = note_handler
+                .create_note_with_body(&body)
+                .await
+                .map_err(|err| err.to_string())?;
+            show(cx, vec![note])
+        }
+        Request::Show { id } => {
+            let note = note_handler
+                .run(move |handler| {
+                    // A trashed note has to be restored before it is shown.
+                    let note = handler
+                        .get_by_id(&id)?
+                        .filter(|note| note.deleted_at.is_none())
+                        .ok_or_else(|| KernelError::note_not_found(&id))?;
+                    handler.set_note_active(&id, true)?;
+                    Ok(Note {
+                        is_active: true,
+                        ..note
+                    })
+                })
+                .await
+                .map_err(|err| err.to_string())?;
+            show(cx, vec![note])
+        }
+        Request::Hide { id } => {
+            let note = note_handler
+                .run(move |handler| {
+                    handler.set_note_active(&id, false)?;
+                    handler.get_by_id(&id)
+                })
+                .await
+                .map_err(|err| err.to_string())?;
+            hide(cx, note.into_iter().collect())
+        }
+        Request::ShowAll => {
+            let notes = note_handler
+                .show_all_notes()
+                .await
+                .map_err(|err| err.to_string())?;
+            show(cx, notes)
+        }
+        Request::HideAll => {
+            let notes = note_handler
+                .run(|handler| {
+                    let shown = handler
+                        .get_all(NoteSortKey::default())?
+                        .into_iter()
+                        .filter(|note| note.is_active)
+                        .collect::<Vec<_>>();
+                    for note in &shown {
+                        handler.set_note_active(&note.id, false)?;
+                    }
+                    Ok(shown)
+                })
+                .await
+                .map_err(|err| err.to_string())?;
+            hide(cx, notes)
+        }
+        Request::List => note_handler
+            .get_all(NoteSortKey::default())
+            .await
+            .map_err(|err| err.to_string()),
+    }
+}
+
+fn show(cx: &mut AsyncApp, notes: Vec<Note>) -> Result<Vec<Note>, String> {
+    cx.update(|cx| {
+        for note in &notes {
+            add_note(cx, note.clone());
+        }
+    })
+    .map_err(|err| err.to_string())?;
+    Ok(notes)
+}
+
+fn hide(cx: &mut AsyncApp, notes: Vec<Note>) -> Result<Vec<Note>, String> {
+    let notes = notes
+        .into_iter()
+        .map(|note| Note {
+            is_active: false,
+            ..note
+        })
+        .collect::<Vec<_>>();
+    cx.update(|cx| {
+        for note in &notes {
+            push_closed_note(cx, &note.id);
+            EditorDelegate::close_note(cx, &note.id);
+        }
+    })
+    .map_err(|err| err.to_string())?;
+    Ok(notes)
+}
+
+fn note_info(note: Note) -> NoteInfo {
+    NoteInfo {
+        id: note.id,
+        body: note.body,
+        is_active: note.is_active,
+    }
+}
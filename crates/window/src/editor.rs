@@ -1,18 +1,33 @@
-use std::cmp::max;
+use std::{cmp::max, time::Duration};
 
+use debounce::{Bouncer, Throttle, timer::Timer};
 use gpui::{
-    App, AppContext, Context, Entity, FocusHandle, Focusable, InteractiveElement, KeyBinding,
-    ParentElement, Pixels, Render, Size, Styled, Window, WindowBounds, WindowOptions, actions,
-    black, div, hsla,
+    App, AppContext, Bounds, Context, Entity, FocusHandle, Focusable, InteractiveElement,
+    KeyBinding, ParentElement, Pixels, Render, Size, Styled, Subscription, Window, WindowBounds,
+    WindowOptions, actions, black, div, hsla,
 };
 use gpui_component::input::{InputEvent, TextInput};
+use handler::async_note_handler::Reply;
 use kernel::model::note::{UpdateNoteBodyEvent, UpdateNoteBoundsEvent};
-use registry::{add_note, global_model::app_handler::AppHandler};
+use registry::{
+    add_note,
+    global_model::{app_handler::AppHandler, note_store::Note},
+    pop_closed_note, push_closed_note,
+};
+
+use crate::{
+    notification::{ErrorBanner, ShowError, notify_error, report},
+    revisions::RevisionWindow,
+};
 
 const CONTEXT: &str = "Editor";
 const WINDOW_MIN_WIDTH: f64 = 30.;
 const WINDOW_MIN_HEIGHT: f64 = 5.;
 const RESIZE_STEP: f64 = 100.;
+/// How long typing has to pause before the body is written to disk.
+const PERSIST_DELAY: Duration = Duration::from_millis(500);
+/// How often the bounds of a window being dragged are written to disk.
+const BOUNDS_INTERVAL: Duration = Duration::from_millis(250);
 
 enum Direction {
     Up,
@@ -26,6 +41,9 @@ actions!(
     [
         NewEditor,
         CloseEditor,
+        ShowAllNotes,
+        ReopenLastClosed,
+        ShowRevisions,
         MoveWindowUp,
         MoveWindowDown,
         MoveWindowRight,
@@ -47,6 +65,12 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("cmd-n", NewEditor, Some(CONTEXT)),
         #[cfg(target_os = "macos")]
         KeyBinding::new("cmd-w", CloseEditor, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-y", ShowRevisions, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-a", ShowAllNotes, None),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-t", ReopenLastClosed, None),
         KeyBinding::new("ctrl-k", MoveWindowUp, Some(CONTEXT)),
         KeyBinding::new("ctrl-j", MoveWindowDown, Some(CONTEXT)),
         KeyBinding::new("ctrl-l", MoveWindowRight, Some(CONTEXT)),
@@ -68,55 +92,156 @@ pub fn init(cx: &mut App) {
         #[cfg(target_os = "macos")]
         KeyBinding::new("cmd-shift-h", ShrinkRight, Some(CONTEXT)),
     ]);
+
+    cx.on_action(show_all_notes);
+    cx.on_action(reopen_last_closed);
+}
+
+fn show_all_notes(_: &ShowAllNotes, cx: &mut App) {
+    let shown = cx.global::<AppHandler>().note_handler().show_all_notes();
+    cx.spawn(async move |cx| match shown.await {
+        Ok(notes) => {
+            cx.update(|cx| {
+                for note in notes {
+                    add_note(cx, note);
+                }
+            })
+            .ok();
+        }
+        Err(err) => {
+            cx.update(|cx| notify_error(cx, &err)).ok();
+        }
+    })
+    .detach();
+}
+
+fn reopen_last_closed(_: &ReopenLastClosed, cx: &mut App) {
+    let note_handler = cx.global::<AppHandler>().note_handler();
+
+    cx.spawn(async move |cx| {
+        // Skip notes that were deleted or reopened some other way since.
+        while let Ok(Some(id)) = cx.update(pop_closed_note) {
+            let reopened = note_handler
+                .run(move |handler| {
+                    let Some(note) = handler
+                        .get_by_id(&id)?
+                        .filter(|note| !note.is_active && note.deleted_at.is_none())
+                    else {
+                        return Ok(None);
+                    };
+                    handler.set_note_active(&id, true)?;
+                    Ok(Some(Note {
+                        is_active: true,
+                        ..note
+                    }))
+                })
+                .await;
+            match reopened {
+                Ok(Some(note)) => {
+                    cx.update(|cx| add_note(cx, note)).ok();
+                    return;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    cx.update(|cx| notify_error(cx, &err)).ok();
+                    return;
+                }
+            }
+        }
+    })
+    .detach();
 }
 
 pub struct Editor {
     id: String,
     focus_handle: FocusHandle,
     input: Entity<TextInput>,
+    body_writes: Bouncer<Reply<()>, UpdateNoteBodyEvent>,
+    bounds_writes: Throttle<Reply<()>, UpdateNoteBoundsEvent>,
+    body_timer: Timer,
+    bounds_timer: Timer,
+    errors: ErrorBanner,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl Editor {
-    pub fn view(window: &mut Window, cx: &mut App, id: &str) -> Entity<Self> {
-        cx.new(|cx| Self::new(window, cx, id))
+    pub fn view(window: &mut Window, cx: &mut App, id: &str, body: String) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx, id, body))
     }
 
-    pub fn new(window: &mut Window, cx: &mut Context<Self>, id: &str) -> Self {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>, id: &str, body: String) -> Self {
         let focus_handle = cx.focus_handle();
 
-        cx.observe_window_bounds(window, |this, window, cx| {
-            let bounds = window.bounds();
-            cx.global::<AppHandler>()
-                .note_handler()
-                .update_note_bounds(UpdateNoteBoundsEvent {
-                    id: this.id.clone(),
-                    bounds,
-                });
-        })
-        .detach();
+        let mut _subscriptions = vec![
+            cx.observe_window_bounds(window, |this, window, cx| {
+                this.write_bounds(window.bounds(), cx);
+            }),
+            // Quitting waits for the last writes to reach the database.
+            cx.on_app_quit(|this, _| {
+                this.body_writes.flush();
+                this.bounds_writes.flush();
+                let replies = [
+                    this.body_writes.take_result(),
+                    this.bounds_writes.take_result(),
+                ];
+                async move {
+                    for reply in replies.into_iter().flatten() {
+                        if let Err(err) = reply.await {
+                            report(&err);
+                        }
+                    }
+                }
+            }),
+        ];
 
         let input = cx.new(|cx| {
-            let note = cx
-                .global::<AppHandler>()
-                .note_handler()
-                .get_by_id(id)
-                .unwrap();
-
             let mut input = TextInput::new(window, cx)
                 .multi_line()
                 .h_full()
                 .appearance(false);
-            input.set_text(note.body, window, cx);
+            input.set_text(body, window, cx);
             input.focus(window, cx);
             input
         });
-        cx.subscribe_in(&input, window, Self::on_input).detach();
+        _subscriptions.push(cx.subscribe_in(&input, window, Self::on_input));
+
+        let note_handler = cx.global::<AppHandler>().note_handler();
+        let body_writes = Bouncer::new(PERSIST_DELAY).with_func({
+            let note_handler = note_handler.clone();
+            move |event| note_handler.update_note_body(event)
+        });
+        let bounds_writes = Throttle::new(BOUNDS_INTERVAL)
+            .with_func(move |event| note_handler.update_note_bounds(event));
 
         Self {
             id: id.to_string(),
             focus_handle,
             input,
+            body_writes,
+            bounds_writes,
+            body_timer: Timer::default(),
+            bounds_timer: Timer::default(),
+            errors: ErrorBanner::default(),
+            _subscriptions,
+        }
+    }
+
+    pub fn note_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn set_body(&mut self, body: String, window: &mut Window, cx: &mut Context<Self>) {
+        self.input
+            .update(cx, |input, cx| input.set_text(body, window, cx));
+    }
+
+    /// Shows `body` as changed by another process, unless there are edits
+    /// here still to be written; those overwrite it once they are.
+    pub fn sync_body(&mut self, body: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if self.body_writes.is_pending() || self.input.read(cx).text().to_string() == body {
+            return;
         }
+        self.set_body(body.to_string(), window, cx);
     }
 
     pub fn on_input(
@@ -127,25 +252,86 @@ impl Editor {
         cx: &mut Context<Self>,
     ) {
         if let InputEvent::Change(text) = event {
-            cx.global::<AppHandler>()
-                .note_handler()
-                .update_note_body(UpdateNoteBodyEvent {
-                    id: self.id.to_string(),
-                    body: text.to_string(),
-                });
+            self.body_writes.call(UpdateNoteBodyEvent {
+                id: self.id.to_string(),
+                body: text.to_string(),
+            });
+            self.body_timer
+                .schedule(cx, |this| &mut this.body_writes, Self::check_writes);
         };
     }
 
+    fn write_bounds(&mut self, bounds: Bounds<Pixels>, cx: &mut Context<Self>) {
+        self.bounds_writes.call(UpdateNoteBoundsEvent {
+            id: self.id.clone(),
+            bounds,
+        });
+        self.bounds_timer
+            .schedule(cx, |this| &mut this.bounds_writes, Self::check_writes);
+    }
+
+    /// Writes `bounds` right away, superseding any pending write from
+    /// dragging the window.
+    fn write_bounds_now(&mut self, bounds: Bounds<Pixels>, cx: &mut Context<Self>) {
+        self.write_bounds(bounds, cx);
+        self.bounds_writes.flush();
+        self.check_writes(cx);
+    }
+
+    /// Drops the writes still pending, for a note that is gone, so that
+    /// closing the window does not write into the trash.
+    pub fn discard_writes(&mut self) {
+        self.body_writes.cancel();
+        self.bounds_writes.cancel();
+    }
+
+    pub fn flush_writes(&mut self, cx: &mut Context<Self>) {
+        self.body_writes.flush();
+        self.bounds_writes.flush();
+        self.check_writes(cx);
+    }
+
+    /// Shows the error of the latest body or bounds write, once it is known
+    /// to have failed.
+    fn check_writes(&mut self, cx: &mut Context<Self>) {
+        let replies = [
+            self.body_writes.take_result(),
+            self.bounds_writes.take_result(),
+        ];
+        for reply in replies.into_iter().flatten() {
+            self.on_reply(reply, cx, |_, (), _| {});
+        }
+    }
+
     fn new_editor(&mut self, _: &NewEditor, _window: &mut Window, cx: &mut Context<Self>) {
         let note = cx.global::<AppHandler>().note_handler().create_note();
-        add_note(cx, note);
+        self.on_reply(note, cx, |_, note, cx| add_note(cx, note));
     }
 
     fn close_editor(&mut self, _: &CloseEditor, window: &mut Window, cx: &mut Context<Self>) {
-        cx.global::<AppHandler>()
-            .note_handler()
-            .toggle_note_active(&self.id.to_string());
-        window.remove_window();
+        self.flush_writes(cx);
+        let note_handler = cx.global::<AppHandler>().note_handler();
+        let snapshot = note_handler.snapshot_note(&self.id);
+        let hidden = note_handler.set_note_active(&self.id, false);
+        cx.spawn_in(window, async move |this, cx| {
+            // Losing the snapshot is no reason to keep the window open.
+            if let Err(err) = snapshot.await {
+                cx.update(|_, cx| notify_error(cx, &err)).ok();
+            }
+            let hidden = hidden.await;
+            this.update_in(cx, |this, window, cx| {
+                if this.ok_or_show(hidden, cx).is_some() {
+                    push_closed_note(cx, &this.id);
+                    window.remove_window();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn show_revisions(&mut self, _: &ShowRevisions, _window: &mut Window, cx: &mut Context<Self>) {
+        RevisionWindow::open(cx, &self.id);
     }
 
     fn move_window_up(&mut self, _: &MoveWindowUp, window: &mut Window, cx: &mut Context<Self>) {
@@ -179,7 +365,7 @@ impl Editor {
         self.move_winow(Direction::Left, window, cx);
     }
 
-    fn move_winow(&self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
+    fn move_winow(&mut self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
         let mut bounds = window.bounds();
         match direction {
             Direction::Up => bounds.origin.y -= Pixels::from(RESIZE_STEP),
@@ -194,15 +380,11 @@ impl Editor {
             ..Default::default()
         };
 
-        cx.global::<AppHandler>()
-            .note_handler()
-            .update_note_bounds(UpdateNoteBoundsEvent {
-                id: self.id.clone(),
-                bounds,
-            });
+        self.write_bounds_now(bounds, cx);
 
         window.remove_window();
-        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id))
+        let body = self.input.read(cx).text().to_string();
+        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id, body))
             .unwrap();
     }
 
@@ -222,7 +404,7 @@ impl Editor {
         self.inflate(Direction::Left, window, cx);
     }
 
-    fn inflate(&self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
+    fn inflate(&mut self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
         let mut bounds = window.bounds();
         let Size { width, height } = window.viewport_size();
 
@@ -252,14 +434,10 @@ impl Editor {
             ..Default::default()
         };
 
-        cx.global::<AppHandler>()
-            .note_handler()
-            .update_note_bounds(UpdateNoteBoundsEvent {
-                id: self.id.clone(),
-                bounds,
-            });
+        self.write_bounds_now(bounds, cx);
         window.remove_window();
-        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id))
+        let body = self.input.read(cx).text().to_string();
+        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id, body))
             .unwrap();
     }
 
@@ -279,7 +457,7 @@ impl Editor {
         self.shrink(Direction::Left, window, cx);
     }
 
-    fn shrink(&self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
+    fn shrink(&mut self, direction: Direction, window: &mut Window, cx: &mut Context<Self>) {
         let mut bounds = window.bounds();
         let Size { width, height } = window.viewport_size();
 
@@ -320,18 +498,20 @@ impl Editor {
             window_bounds: Some(WindowBounds::Windowed(bounds)),
             ..Default::default()
         };
-        cx.global::<AppHandler>()
-            .note_handler()
-            .update_note_bounds(UpdateNoteBoundsEvent {
-                id: self.id.clone(),
-                bounds,
-            });
+        self.write_bounds_now(bounds, cx);
         window.remove_window();
-        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id))
+        let body = self.input.read(cx).text().to_string();
+        cx.open_window(options, |window, cx| Self::view(window, cx, &self.id, body))
             .unwrap();
     }
 }
 
+impl ShowError for Editor {
+    fn error_banner(&mut self) -> &mut ErrorBanner {
+        &mut self.errors
+    }
+}
+
 impl Focusable for Editor {
     fn focus_handle(&self, _cx: &App) -> FocusHandle {
         self.focus_handle.clone()
@@ -349,6 +529,7 @@ impl Render for Editor {
             .track_focus(&self.focus_handle.clone())
             .on_action(cx.listener(Self::new_editor))
             .on_action(cx.listener(Self::close_editor))
+            .on_action(cx.listener(Self::show_revisions))
             .on_action(cx.listener(Self::move_window_up))
             .on_action(cx.listener(Self::move_window_down))
             .on_action(cx.listener(Self::move_window_right))
@@ -369,6 +550,10 @@ impl Render for Editor {
             .h_full()
             .items_center()
             .justify_center()
+            .children(
+                self.errors
+                    .render(cx.listener(|this, _, _, cx| this.dismiss_error(cx))),
+            )
             .child(self.input.clone())
     }
 }
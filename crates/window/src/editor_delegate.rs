@@ -1,5 +1,8 @@
-use gpui::App;
-use registry::global_model::note_store::{Note, NoteStore};
+use gpui::{App, WindowHandle};
+use registry::{
+    add_note,
+    global_model::note_store::{Note, NoteStore},
+};
 
 use crate::{
     editor::Editor,
@@ -25,7 +28,10 @@ impl EditorDelegate {
         }
 
         cx.observe_global::<NoteStore>(|cx| {
-            let notes = cx.global::<NoteStore>().new_notes.clone();
+            if cx.global::<NoteStore>().new_notes.is_empty() {
+                return;
+            }
+            let notes = std::mem::take(&mut cx.global_mut::<NoteStore>().new_notes);
             for note in notes {
                 Self::render_note(cx, &note);
             }
@@ -33,18 +39,118 @@ impl EditorDelegate {
         .detach();
     }
 
+    /// Closes the editor window of a note, if it is open.
+    pub fn close_note(cx: &mut App, id: &str) {
+        if let Some(handle) = Self::find_window(cx, id) {
+            handle
+                .update(cx, |_, window, _| window.remove_window())
+                .ok();
+        }
+    }
+
+    /// Writes the pending edits of a note now, if its editor is open, so
+    /// that they land before anything queued after them.
+    pub fn flush_note(cx: &mut App, id: &str) {
+        if let Some(handle) = Self::find_window(cx, id) {
+            handle
+                .update(cx, |editor, _, cx| editor.flush_writes(cx))
+                .ok();
+        }
+    }
+
+    /// Closes the editor window of a note that is gone, dropping the writes
+    /// it still has pending.
+    pub fn discard_note(cx: &mut App, id: &str) {
+        if let Some(handle) = Self::find_window(cx, id) {
+            handle
+                .update(cx, |editor, window, _| {
+                    editor.discard_writes();
+                    window.remove_window();
+                })
+                .ok();
+        }
+    }
+
+    /// Shows the current body of a note in its editor, if it is open.
+    pub fn reload_note(cx: &mut App, note: &Note) {
+        if let Some(handle) = Self::find_window(cx, &note.id) {
+            handle
+                .update(cx, |editor, window, cx| {
+                    editor.set_body(note.body.clone(), window, cx)
+                })
+                .ok();
+        }
+    }
+
+    /// Brings the editors up to date with `notes`, as read back after
+    /// another process changed them: editors of notes that were hidden or
+    /// deleted there close, notes shown there open, and bodies edited there
+    /// replace what an editor shows, unless it has edits of its own that are
+    /// still to be written.
+    pub fn sync_notes(cx: &mut App, notes: &[Note]) {
+        for handle in Self::editor_windows(cx) {
+            let Ok(id) = handle.read(cx).map(|editor| editor.note_id().to_string()) else {
+                continue;
+            };
+            match notes.iter().find(|note| note.id == id) {
+                Some(note) if note.is_active => handle
+                    .update(cx, |editor, window, cx| {
+                        editor.sync_body(&note.body, window, cx)
+                    })
+                    .ok(),
+                // Hidden elsewhere: the pending edits are still written.
+                Some(_) => handle
+                    .update(cx, |_, window, _| window.remove_window())
+                    .ok(),
+                // Trashed or purged elsewhere.
+                None => handle
+                    .update(cx, |editor, window, _| {
+                        editor.discard_writes();
+                        window.remove_window();
+                    })
+                    .ok(),
+            };
+        }
+
+        for note in notes {
+            if note.is_active && Self::find_window(cx, &note.id).is_none() {
+                add_note(cx, note.clone());
+            }
+        }
+    }
+
     fn render_note(cx: &mut App, note: &Note) {
         if !note.is_active {
             return;
         }
 
+        if let Some(handle) = Self::find_window(cx, &note.id) {
+            handle
+                .update(cx, |_, window, _| window.activate_window())
+                .ok();
+            return;
+        }
+
         let location = Location::new(note.location_x, note.location_y);
         let size = WindowSize::new(note.width, note.height);
         cx.open_window(make_editor_option(location, size), |window, cx| {
-            Editor::view(window, cx, &note.id)
+            Editor::view(window, cx, &note.id, note.body.clone())
         })
         .unwrap();
     }
+
+    fn find_window(cx: &App, id: &str) -> Option<WindowHandle<Editor>> {
+        Self::editor_windows(cx)
+            .into_iter()
+            .find(|window| window.read(cx).is_ok_and(|editor| editor.note_id() == id))
+    }
+
+    fn editor_windows(cx: &App) -> Vec<WindowHandle<Editor>> {
+        cx.windows()
+            .into_iter()
+            .filter_map(|window| window.downcast::<Editor>())
+            .collect()
+    }
 }
 
 impl Default for EditorDelegate {
@@ -1,13 +1,36 @@
 use editor_delegate::EditorDelegate;
 use gpui::App;
 
+pub mod backup;
+#[cfg(unix)]
+pub mod control;
 pub mod editor;
 pub mod editor_delegate;
 pub mod list;
+mod notification;
+pub mod recovery;
+pub mod revisions;
+pub mod snapshots;
+mod watcher;
 mod window_options;
 
+/// Sets up the windows once the notes are open, whether at startup or after
+/// a recovery.
 pub fn init(cx: &mut App) {
+    backup::init(cx);
     editor::init(cx);
+    list::init(cx);
+    watcher::init(cx);
+
+    // Other processes only drive an instance whose notes they can change.
+    #[cfg(unix)]
+    {
+        let config = cx.global::<registry::config::Config>();
+        if !config.ephemeral && !config.read_only {
+            let socket = ::control::socket_path(&config.db_path);
+            control::init(cx, socket);
+        }
+    }
 
     EditorDelegate::new().render_notes(cx);
 }
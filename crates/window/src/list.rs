@@ -1,3 +1,467 @@
-pub struct ListWindow {}
+use chrono::Local;
+use gpui::{
+    App, AppContext, Bounds, Context, Entity, FocusHandle, Focusable, FontWeight,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, TitlebarOptions, Window, WindowBounds,
+    WindowOptions, actions, black, div, hsla, px, size, white,
+};
+use gpui_component::{
+    Sizable,
+    button::{Button, ButtonVariants},
+    input::{InputEvent, TextInput},
+};
+use handler::model::search_result::SearchResult;
+use kernel::model::note::NoteSortKey;
+use registry::{
+    add_note,
+    global_model::{app_handler::AppHandler, note_store::Note},
+    push_closed_note,
+};
 
-impl ListWindow {}
+use crate::{
+    editor_delegate::EditorDelegate,
+    notification::{ErrorBanner, ShowError},
+};
+
+const CONTEXT: &str = "ListWindow";
+const WINDOW_WIDTH: f32 = 420.;
+const WINDOW_HEIGHT: f32 = 560.;
+
+actions!(list, [OpenNoteList]);
+
+pub fn init(cx: &mut App) {
+    cx.on_action(|_: &OpenNoteList, cx| ListWindow::open(cx));
+}
+
+pub struct ListWindow {
+    focus_handle: FocusHandle,
+    search: Entity<TextInput>,
+    notes: Vec<Note>,
+    sort: NoteSortKey,
+    show_trash: bool,
+    results: Option<Vec<SearchResult>>,
+    errors: ErrorBanner,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ListWindow {
+    /// Focuses the list window, opening it first if needed.
+    pub fn open(cx: &mut App) {
+        let existing = cx
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<Self>());
+        if let Some(handle) = existing {
+            handle
+                .update(cx, |_, window, _| window.activate_window())
+                .ok();
+            return;
+        }
+
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Notes".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(WINDOW_WIDTH), px(WINDOW_HEIGHT)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        cx.open_window(options, |window, cx| Self::view(window, cx))
+            .unwrap();
+    }
+
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| TextInput::new(window, cx).placeholder("Search notes"));
+
+        let _subscriptions = vec![
+            cx.subscribe_in(&search, window, Self::on_search),
+            // Editors change notes behind our back (closing one hides it),
+            // so refresh whenever the list comes to the front.
+            cx.observe_window_activation(window, |this, window, cx| {
+                if window.is_window_active() {
+                    this.reload(cx);
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            search,
+            notes: vec![],
+            sort: NoteSortKey::Updated,
+            show_trash: false,
+            results: None,
+            errors: ErrorBanner::default(),
+            _subscriptions,
+        };
+        this.reload(cx);
+        this
+    }
+
+    /// Reloads the list. Requests run in order, so the list reflects every
+    /// change requested before the reload.
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let note_handler = cx.global::<AppHandler>().note_handler();
+        let show_trash = self.show_trash;
+        let sort = self.sort;
+        let query = self.search.read(cx).text().to_string();
+        let loaded = note_handler.run(move |handler| {
+            if show_trash {
+                return Ok((handler.get_trash()?, None));
+            }
+            let notes = handler.get_all(sort)?;
+            let results = if query.trim().is_empty() {
+                None
+            } else {
+                Some(handler.search_notes(&query)?)
+            };
+            Ok((notes, results))
+        });
+        self.on_reply(loaded, cx, |this, (notes, results), cx| {
+            this.notes = notes;
+            this.results = results;
+            cx.notify();
+        });
+    }
+
+    fn on_search(
+        &mut self,
+        _: &Entity<TextInput>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change(_) = event {
+            self.reload(cx);
+        }
+    }
+
+    fn cycle_sort(&mut self, cx: &mut Context<Self>) {
+        self.sort = match self.sort {
+            NoteSortKey::Updated => NoteSortKey::Created,
+            NoteSortKey::Created => NoteSortKey::Title,
+            NoteSortKey::Title => NoteSortKey::Updated,
+        };
+        self.reload(cx);
+    }
+
+    fn toggle_trash(&mut self, cx: &mut Context<Self>) {
+        self.show_trash = !self.show_trash;
+        self.reload(cx);
+    }
+
+    fn create_note(&mut self, cx: &mut Context<Self>) {
+        let note = cx.global::<AppHandler>().note_handler().create_note();
+        self.on_reply(note, cx, |_, note, cx| add_note(cx, note));
+        self.reload(cx);
+    }
+
+    fn reopen_note(&mut self, id: &str, cx: &mut Context<Self>) {
+        let id = id.to_string();
+        let note = cx
+            .global::<AppHandler>()
+            .note_handler()
+            .run(move |handler| {
+                handler.set_note_active(&id, true)?;
+                handler.get_by_id(&id)
+            });
+        self.on_reply(note, cx, |_, note, cx| {
+            if let Some(note) = note {
+                add_note(cx, note);
+            }
+        });
+        self.reload(cx);
+    }
+
+    fn hide_note(&mut self, id: &str, cx: &mut Context<Self>) {
+        let hidden = cx
+            .global::<AppHandler>()
+            .note_handler()
+            .set_note_active(id, false);
+        let id = id.to_string();
+        self.on_reply(hidden, cx, move |_, (), cx| {
+            push_closed_note(cx, &id);
+            EditorDelegate::close_note(cx, &id);
+        });
+        self.reload(cx);
+    }
+
+    fn delete_note(&mut self, id: &str, cx: &mut Context<Self>) {
+        // The last edits go into the trash with the note.
+        EditorDelegate::flush_note(cx, id);
+        let deleted = cx.global::<AppHandler>().note_handler().delete_note(id);
+        let id = id.to_string();
+        self.on_reply(deleted, cx, move |_, (), cx| {
+            EditorDelegate::discard_note(cx, &id)
+        });
+        self.reload(cx);
+    }
+
+    fn restore_note(&mut self, id: &str, cx: &mut Context<Self>) {
+        let note = cx.global::<AppHandler>().note_handler().restore_note(id);
+        self.on_reply(note, cx, |_, note, cx| add_note(cx, note));
+        self.reload(cx);
+    }
+
+    fn purge_note(&mut self, id: &str, cx: &mut Context<Self>) {
+        let purged = cx.global::<AppHandler>().note_handler().purge_note(id);
+        self.on_reply(purged, cx, |_, (), _| {});
+        self.reload(cx);
+    }
+
+    fn render_trashed_row(
+        &self,
+        ix: usize,
+        note: &Note,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let deleted = note
+            .deleted_at
+            .unwrap_or(note.updated_at)
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        let id = note.id.clone();
+        let restore = Button::new(("restore", ix))
+            .label("Restore")
+            .small()
+            .ghost()
+            .on_click(cx.listener(move |this, _, _, cx| this.restore_note(&id, cx)));
+        let id = note.id.clone();
+        let purge = Button::new(("purge", ix))
+            .label("Delete Forever")
+            .small()
+            .ghost()
+            .on_click(cx.listener(move |this, _, _, cx| this.purge_note(&id, cx)));
+
+        div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(hsla(0., 0., 0., 0.1))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .child(SharedString::from(note.title())),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(hsla(0., 0., 0., 0.5))
+                            .child(SharedString::from(format!("Deleted {deleted}"))),
+                    ),
+            )
+            .child(restore)
+            .child(purge)
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        note: &Note,
+        result: Option<&SearchResult>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let state = if note.is_active { "Visible" } else { "Hidden" };
+        let modified = note
+            .updated_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        let toggle = if note.is_active {
+            let id = note.id.clone();
+            Button::new(("hide", ix))
+                .label("Hide")
+                .small()
+                .ghost()
+                .on_click(cx.listener(move |this, _, _, cx| this.hide_note(&id, cx)))
+        } else {
+            let id = note.id.clone();
+            Button::new(("open", ix))
+                .label("Open")
+                .small()
+                .ghost()
+                .on_click(cx.listener(move |this, _, _, cx| this.reopen_note(&id, cx)))
+        };
+        let id = note.id.clone();
+        let delete = Button::new(("delete", ix))
+            .label("Delete")
+            .small()
+            .ghost()
+            .on_click(cx.listener(move |this, _, _, cx| this.delete_note(&id, cx)));
+
+        div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(hsla(0., 0., 0., 0.1))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .child(SharedString::from(note.title())),
+                    )
+                    .children(result.map(render_snippet))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(hsla(0., 0., 0., 0.5))
+                            .child(SharedString::from(format!("{state} · Edited {modified}"))),
+                    ),
+            )
+            .child(toggle)
+            .child(delete)
+    }
+}
+
+/// Renders a search snippet with the matched terms emphasized.
+fn render_snippet(result: &SearchResult) -> impl IntoElement {
+    let mut segments = vec![];
+    let mut offset = 0;
+    for range in &result.highlights {
+        if offset < range.start {
+            segments.push((false, &result.snippet[offset..range.start]));
+        }
+        segments.push((true, &result.snippet[range.clone()]));
+        offset = range.end;
+    }
+    if offset < result.snippet.len() {
+        segments.push((false, &result.snippet[offset..]));
+    }
+
+    div()
+        .flex()
+        .flex_row()
+        .flex_wrap()
+        .text_sm()
+        .children(segments.into_iter().map(|(highlighted, text)| {
+            let segment = div().child(SharedString::from(text.to_string()));
+            if highlighted {
+                segment
+                    .bg(hsla(0.15, 0.96, 0.75, 1.))
+                    .font_weight(FontWeight::BOLD)
+            } else {
+                segment
+            }
+        }))
+}
+
+impl ShowError for ListWindow {
+    fn error_banner(&mut self) -> &mut ErrorBanner {
+        &mut self.errors
+    }
+}
+
+impl Focusable for ListWindow {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ListWindow {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let rows = if self.show_trash {
+            self.notes
+                .iter()
+                .enumerate()
+                .map(|(ix, note)| self.render_trashed_row(ix, note, cx).into_any_element())
+                .collect::<Vec<_>>()
+        } else {
+            let rows = match &self.results {
+                Some(results) => results
+                    .iter()
+                    .map(|result| (result.note.clone(), Some(result.clone())))
+                    .collect::<Vec<_>>(),
+                None => self.notes.iter().map(|note| (note.clone(), None)).collect(),
+            };
+            rows.iter()
+                .enumerate()
+                .map(|(ix, (note, result))| {
+                    self.render_row(ix, note, result.as_ref(), cx)
+                        .into_any_element()
+                })
+                .collect()
+        };
+
+        div()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(white())
+            .text_color(black())
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_2()
+                    .p_2()
+                    .child(div().flex_1().child(self.search.clone()))
+                    .child(
+                        Button::new("sort")
+                            .label(match self.sort {
+                                NoteSortKey::Updated => "Recent",
+                                NoteSortKey::Created => "Created",
+                                NoteSortKey::Title => "Title",
+                            })
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _, cx| this.cycle_sort(cx))),
+                    )
+                    .child(
+                        Button::new("trash")
+                            .label(if self.show_trash { "Notes" } else { "Trash" })
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _, cx| this.toggle_trash(cx))),
+                    )
+                    .child(
+                        Button::new("new-note")
+                            .label("New")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _, cx| this.create_note(cx))),
+                    ),
+            )
+            .children(
+                self.errors
+                    .render(cx.listener(|this, _, _, cx| this.dismiss_error(cx))),
+            )
+            .child(
+                div()
+                    .id("notes")
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+    }
+}
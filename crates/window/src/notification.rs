@@ -0,0 +1,206 @@
+use std::future::Future;
+
+use gpui::{
+    App, AppContext, Bounds, ClickEvent, Context, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, ParentElement, Render, SharedString, Styled, TitlebarOptions, Window,
+    WindowBounds, WindowOptions, black, div, hsla, prelude::FluentBuilder, px, size, white,
+};
+use gpui_component::{
+    Sizable,
+    button::{Button, ButtonVariants},
+};
+use kernel::error::{KernelError, Result};
+
+const NOTICE_CONTEXT: &str = "NoticeWindow";
+const NOTICE_WIDTH: f32 = 420.;
+const NOTICE_HEIGHT: f32 = 160.;
+
+/// Logs a failure, for when the app is going away and nothing can be shown.
+pub fn report(err: &KernelError) {
+    eprintln!("sticky: {err}");
+}
+
+/// Shows a failure that has no window of its own to be shown in, such as
+/// one of an action from the menu.
+pub fn notify_error(cx: &mut App, err: &KernelError) {
+    report(err);
+    NoticeWindow::show(cx, err.to_string().into(), true);
+}
+
+/// Shows the outcome of an action that has no window of its own.
+pub fn notify(cx: &mut App, message: impl Into<SharedString>) {
+    NoticeWindow::show(cx, message.into(), false);
+}
+
+/// A small window for notices that no other window can show. A new notice
+/// replaces the one shown instead of opening another window.
+pub struct NoticeWindow {
+    focus_handle: FocusHandle,
+    message: SharedString,
+    is_error: bool,
+}
+
+impl NoticeWindow {
+    fn show(cx: &mut App, message: SharedString, is_error: bool) {
+        let existing = cx
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<Self>());
+        if let Some(handle) = existing {
+            let shown = handle.update(cx, |this, window, cx| {
+                this.message = message.clone();
+                this.is_error = is_error;
+                cx.notify();
+                window.activate_window();
+            });
+            if shown.is_ok() {
+                return;
+            }
+        }
+
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Sticky".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(NOTICE_WIDTH), px(NOTICE_HEIGHT)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        cx.open_window(options, |_, cx| {
+            cx.new(|cx| Self {
+                focus_handle: cx.focus_handle(),
+                message,
+                is_error,
+            })
+        })
+        .ok();
+    }
+}
+
+impl Focusable for NoticeWindow {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for NoticeWindow {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .key_context(NOTICE_CONTEXT)
+            .track_focus(&self.focus_handle)
+            .flex()
+            .flex_col()
+            .size_full()
+            .gap_2()
+            .p_3()
+            .bg(white())
+            .text_color(black())
+            .child(
+                div()
+                    .flex_1()
+                    .p_2()
+                    .text_sm()
+                    .when(self.is_error, |message| message.bg(hsla(0., 0.7, 0.9, 1.)))
+                    .child(self.message.clone()),
+            )
+            .child(
+                div().flex().flex_row().justify_end().child(
+                    Button::new("ok")
+                        .label("OK")
+                        .small()
+                        .primary()
+                        .on_click(|_, window, _| window.remove_window()),
+                ),
+            )
+    }
+}
+
+/// The latest failure of a window, shown as a dismissible banner instead of
+/// taking the app down.
+#[derive(Default)]
+pub struct ErrorBanner {
+    message: Option<SharedString>,
+}
+
+impl ErrorBanner {
+    pub fn show(&mut self, err: &KernelError) {
+        report(err);
+        self.message = Some(err.to_string().into());
+    }
+
+    pub fn dismiss(&mut self) {
+        self.message = None;
+    }
+
+    pub fn render(
+        &self,
+        on_dismiss: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> Option<impl IntoElement> {
+        let message = self.message.clone()?;
+        Some(
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .gap_2()
+                .px_3()
+                .py_1()
+                .text_sm()
+                .bg(hsla(0., 0.7, 0.9, 1.))
+                .child(div().flex_1().child(message))
+                .child(
+                    Button::new("dismiss-error")
+                        .label("Dismiss")
+                        .xsmall()
+                        .ghost()
+                        .on_click(on_dismiss),
+                ),
+        )
+    }
+}
+
+/// Views with an [`ErrorBanner`].
+pub trait ShowError: Sized + 'static {
+    fn error_banner(&mut self) -> &mut ErrorBanner;
+
+    /// Returns the value of `result`, or shows its error and returns `None`.
+    fn ok_or_show<T>(&mut self, result: Result<T>, cx: &mut Context<Self>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.error_banner().show(&err);
+                cx.notify();
+                None
+            }
+        }
+    }
+
+    /// Waits for `reply` without holding up the window, then hands its value
+    /// to `then`, or shows its error.
+    fn on_reply<T: 'static>(
+        &mut self,
+        reply: impl Future<Output = Result<T>> + 'static,
+        cx: &mut Context<Self>,
+        then: impl FnOnce(&mut Self, T, &mut Context<Self>) + 'static,
+    ) {
+        cx.spawn(async move |this, cx| {
+            let result = reply.await;
+            this.update(cx, |this, cx| {
+                if let Some(value) = this.ok_or_show(result, cx) {
+                    then(this, value, cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn dismiss_error(&mut self, cx: &mut Context<Self>) {
+        self.error_banner().dismiss();
+        cx.notify();
+    }
+}
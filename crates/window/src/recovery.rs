@@ -0,0 +1,217 @@
+use db::error::{DbError, Failure};
+use gpui::{
+    App, AppContext, Bounds, Context, Entity, FocusHandle, Focusable, FontWeight,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled, TitlebarOptions,
+    Window, WindowBounds, WindowOptions, black, div, hsla, px, size, white,
+};
+use gpui_component::{
+    Sizable,
+    button::{Button, ButtonVariants},
+};
+use registry::config::Config;
+
+const CONTEXT: &str = "RecoveryWindow";
+const WINDOW_WIDTH: f32 = 480.;
+const WINDOW_HEIGHT: f32 = 220.;
+
+enum State {
+    /// The database could not be opened; the user picks what to do.
+    Failed {
+        failure: Failure,
+        message: SharedString,
+    },
+    /// A recovery worked and the notes are open; tells the user how.
+    Recovered { message: SharedString },
+}
+
+/// Shown instead of the notes when the database cannot be opened at
+/// startup. Offers to retry, to start over with a fresh database, or to open
+/// the existing one read-only.
+pub struct RecoveryWindow {
+    config: Config,
+    focus_handle: FocusHandle,
+    state: State,
+}
+
+impl RecoveryWindow {
+    pub fn open(cx: &mut App, config: Config, err: DbError) {
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Sticky".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(WINDOW_WIDTH), px(WINDOW_HEIGHT)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        cx.open_window(options, |window, cx| Self::view(window, cx, config, err))
+            .unwrap();
+    }
+
+    pub fn view(_window: &mut Window, cx: &mut App, config: Config, err: DbError) -> Entity<Self> {
+        cx.new(|cx| Self::new(cx, config, err))
+    }
+
+    fn new(cx: &mut Context<Self>, config: Config, err: DbError) -> Self {
+        Self {
+            config,
+            focus_handle: cx.focus_handle(),
+            state: Self::failed(err),
+        }
+    }
+
+    fn failed(err: DbError) -> State {
+        eprintln!("Failed to open the database: {err}");
+        State::Failed {
+            failure: err.failure(),
+            message: err.to_string().into(),
+        }
+    }
+
+    fn retry(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match registry::init(cx, &self.config) {
+            Ok(()) => {
+                crate::init(cx);
+                window.remove_window();
+            }
+            Err(err) => self.state = Self::failed(err),
+        }
+        cx.notify();
+    }
+
+    fn start_fresh(&mut self, cx: &mut Context<Self>) {
+        self.state = match registry::start_fresh(cx, &self.config) {
+            Ok(moved_to) => {
+                crate::init(cx);
+                State::Recovered {
+                    message: format!(
+                        "Started with an empty database. The damaged one was moved to {}.",
+                        moved_to.display()
+                    )
+                    .into(),
+                }
+            }
+            Err(err) => Self::failed(err),
+        };
+        cx.notify();
+    }
+
+    fn open_read_only(&mut self, cx: &mut Context<Self>) {
+        self.state = match registry::open_read_only(cx, &self.config) {
+            Ok(()) => {
+                crate::init(cx);
+                State::Recovered {
+                    message: "Opened the notes read-only. Changes will not be saved until \
+                              Sticky is restarted with a writable database."
+                        .into(),
+                }
+            }
+            Err(err) => Self::failed(err),
+        };
+        cx.notify();
+    }
+
+    fn render_failed(
+        &self,
+        failure: Failure,
+        message: &SharedString,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let title = match failure {
+            Failure::Busy => "The notes database is in use by another program.",
+            Failure::Corrupt => "The notes database is damaged.",
+            Failure::ReadOnly => "The notes database cannot be written to.",
+            Failure::Other => "The notes database could not be opened.",
+        };
+        let retry = Button::new("retry")
+            .label("Try Again")
+            .small()
+            .on_click(cx.listener(|this, _, window, cx| this.retry(window, cx)));
+        let read_only = Button::new("read-only")
+            .label("Open Read-Only")
+            .small()
+            .on_click(cx.listener(|this, _, _, cx| this.open_read_only(cx)));
+        let quit = Button::new("quit")
+            .label("Quit")
+            .small()
+            .ghost()
+            .on_click(|_, _, cx| cx.quit());
+        let start_fresh = (failure == Failure::Corrupt).then(|| {
+            Button::new("start-fresh")
+                .label("Start Fresh")
+                .small()
+                .primary()
+                .on_click(cx.listener(|this, _, _, cx| this.start_fresh(cx)))
+        });
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(div().font_weight(FontWeight::BOLD).child(title))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(hsla(0., 0., 0., 0.6))
+                    .child(message.clone()),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_end()
+                    .gap_2()
+                    .pt_2()
+                    .child(quit)
+                    .child(read_only)
+                    .child(retry)
+                    .children(start_fresh),
+            )
+    }
+
+    fn render_recovered(&self, message: &SharedString) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(message.clone())
+            .child(
+                div().flex().flex_row().justify_end().pt_2().child(
+                    Button::new("ok")
+                        .label("OK")
+                        .small()
+                        .primary()
+                        .on_click(|_, window, _| window.remove_window()),
+                ),
+            )
+    }
+}
+
+impl Focusable for RecoveryWindow {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for RecoveryWindow {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let content = match &self.state {
+            State::Failed { failure, message } => {
+                self.render_failed(*failure, message, cx).into_any_element()
+            }
+            State::Recovered { message } => self.render_recovered(message).into_any_element(),
+        };
+
+        div()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_4()
+            .bg(white())
+            .text_color(black())
+            .child(content)
+    }
+}
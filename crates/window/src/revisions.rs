@@ -0,0 +1,246 @@
+use chrono::Local;
+use gpui::{
+    App, AppContext, Bounds, Context, Entity, FocusHandle, Focusable, FontWeight,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, TitlebarOptions, Window, WindowBounds, WindowOptions,
+    black, div, hsla, prelude::FluentBuilder, px, size, white,
+};
+use gpui_component::{
+    Disableable, Sizable,
+    button::{Button, ButtonVariants},
+};
+use handler::model::{
+    diff::{DiffKind, DiffLine},
+    revision::Revision,
+};
+use registry::global_model::app_handler::AppHandler;
+
+use crate::{
+    editor_delegate::EditorDelegate,
+    notification::{ErrorBanner, ShowError},
+};
+
+const CONTEXT: &str = "RevisionWindow";
+const WINDOW_WIDTH: f32 = 640.;
+const WINDOW_HEIGHT: f32 = 480.;
+
+/// Browses the revision history of a single note and restores old bodies.
+pub struct RevisionWindow {
+    note_id: String,
+    focus_handle: FocusHandle,
+    revisions: Vec<Revision>,
+    selected: Option<usize>,
+    diff: Vec<DiffLine>,
+    errors: ErrorBanner,
+}
+
+impl RevisionWindow {
+    /// Focuses the history of `note_id`, opening a window for it if needed.
+    pub fn open(cx: &mut App, note_id: &str) {
+        let existing = cx
+            .windows()
+            .into_iter()
+            .filter_map(|window| window.downcast::<Self>())
+            .find(|window| window.read(cx).is_ok_and(|this| this.note_id == note_id));
+        if let Some(handle) = existing {
+            handle
+                .update(cx, |this, window, cx| {
+                    this.reload(cx);
+                    window.activate_window();
+                })
+                .ok();
+            return;
+        }
+
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("History".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(WINDOW_WIDTH), px(WINDOW_HEIGHT)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        cx.open_window(options, |window, cx| Self::view(window, cx, note_id))
+            .unwrap();
+    }
+
+    pub fn view(_window: &mut Window, cx: &mut App, note_id: &str) -> Entity<Self> {
+        cx.new(|cx| Self::new(cx, note_id))
+    }
+
+    pub fn new(cx: &mut Context<Self>, note_id: &str) -> Self {
+        let mut this = Self {
+            note_id: note_id.to_string(),
+            focus_handle: cx.focus_handle(),
+            revisions: vec![],
+            selected: None,
+            diff: vec![],
+            errors: ErrorBanner::default(),
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let revisions = cx
+            .global::<AppHandler>()
+            .note_handler()
+            .get_revisions(&self.note_id);
+        self.on_reply(revisions, cx, |this, revisions, cx| {
+            this.revisions = revisions;
+            this.select(0, cx);
+        });
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = (ix < self.revisions.len()).then_some(ix);
+        self.diff = vec![];
+        if let Some(ix) = self.selected {
+            let id = self.revisions[ix].id.clone();
+            let diff = cx
+                .global::<AppHandler>()
+                .note_handler()
+                .diff_with_current(&id);
+            self.on_reply(diff, cx, move |this, diff, cx| {
+                // Ignore the diff of a revision that is no longer selected.
+                if this.selected.map(|ix| &this.revisions[ix].id) == Some(&id) {
+                    this.diff = diff;
+                    cx.notify();
+                }
+            });
+        }
+        cx.notify();
+    }
+
+    fn restore(&mut self, cx: &mut Context<Self>) {
+        let Some(revision) = self.selected.map(|ix| &self.revisions[ix]) else {
+            return;
+        };
+        let note = cx
+            .global::<AppHandler>()
+            .note_handler()
+            .restore_revision(&revision.id);
+        self.on_reply(note, cx, |this, note, cx| {
+            EditorDelegate::reload_note(cx, &note);
+            this.reload(cx);
+        });
+    }
+
+    fn render_diff_line(line: &DiffLine) -> impl IntoElement {
+        let (prefix, background) = match line.kind {
+            DiffKind::Unchanged => (" ", hsla(0., 0., 0., 0.)),
+            DiffKind::Added => ("+", hsla(0.33, 0.6, 0.85, 1.)),
+            DiffKind::Removed => ("-", hsla(0., 0.7, 0.9, 1.)),
+        };
+        div()
+            .px_2()
+            .bg(background)
+            .child(SharedString::from(format!("{prefix} {}", line.text)))
+    }
+}
+
+impl ShowError for RevisionWindow {
+    fn error_banner(&mut self) -> &mut ErrorBanner {
+        &mut self.errors
+    }
+}
+
+impl Focusable for RevisionWindow {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for RevisionWindow {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let revisions = self
+            .revisions
+            .iter()
+            .enumerate()
+            .map(|(ix, revision)| {
+                let created = revision
+                    .created_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S");
+                div()
+                    .id(("revision", ix))
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(hsla(0., 0., 0., 0.1))
+                    .when(self.selected == Some(ix), |row| {
+                        row.bg(hsla(0.15, 0.96, 0.75, 1.))
+                    })
+                    .child(SharedString::from(created.to_string()))
+                    .on_click(cx.listener(move |this, _, _, cx| this.select(ix, cx)))
+            })
+            .collect::<Vec<_>>();
+        let title = if self.revisions.is_empty() {
+            "No revisions yet"
+        } else {
+            "Changes since the selected revision"
+        };
+
+        div()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(white())
+            .text_color(black())
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .justify_between()
+                    .p_2()
+                    .child(div().font_weight(FontWeight::BOLD).child(title))
+                    .child(
+                        Button::new("restore")
+                            .label("Restore")
+                            .small()
+                            .primary()
+                            .disabled(self.selected.is_none())
+                            .on_click(cx.listener(|this, _, _, cx| this.restore(cx))),
+                    ),
+            )
+            .children(
+                self.errors
+                    .render(cx.listener(|this, _, _, cx| this.dismiss_error(cx))),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(
+                        div()
+                            .id("revisions")
+                            .w(px(200.))
+                            .flex()
+                            .flex_col()
+                            .overflow_y_scroll()
+                            .border_r_1()
+                            .border_color(hsla(0., 0., 0., 0.1))
+                            .children(revisions),
+                    )
+                    .child(
+                        div()
+                            .id("diff")
+                            .flex()
+                            .flex_col()
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .text_sm()
+                            .children(self.diff.iter().map(Self::render_diff_line)),
+                    ),
+            )
+    }
+}
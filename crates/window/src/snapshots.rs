@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+
+use chrono::Local;
+use db::{
+    error::DbError,
+    snapshot::{Snapshot, list_snapshots, restore_snapshot},
+};
+use gpui::{
+    App, AppContext, Bounds, Context, Entity, FocusHandle, Focusable, FontWeight,
+    InteractiveElement, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, TitlebarOptions, Window, WindowBounds, WindowOptions,
+    black, div, hsla, prelude::FluentBuilder, px, size, white,
+};
+use gpui_component::{
+    Disableable, Sizable,
+    button::{Button, ButtonVariants},
+};
+use kernel::error::KernelError;
+use registry::{config::Config, global_model::app_handler::AppHandler};
+
+use crate::{
+    notification::{ErrorBanner, ShowError},
+    watcher::reload,
+};
+
+const CONTEXT: &str = "SnapshotWindow";
+const WINDOW_WIDTH: f32 = 480.;
+const WINDOW_HEIGHT: f32 = 400.;
+
+/// Lists the rolling snapshots of the database and puts one of them back.
+pub struct SnapshotWindow {
+    db_path: PathBuf,
+    focus_handle: FocusHandle,
+    snapshots: Vec<Snapshot>,
+    selected: Option<usize>,
+    errors: ErrorBanner,
+}
+
+impl SnapshotWindow {
+    /// Focuses the snapshot window, opening it if needed.
+    pub fn open(cx: &mut App) {
+        let existing = cx
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<Self>());
+        if let Some(handle) = existing {
+            handle
+                .update(cx, |this, window, cx| {
+                    this.reload(cx);
+                    window.activate_window();
+                })
+                .ok();
+            return;
+        }
+
+        let options = WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some("Backups".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(WINDOW_WIDTH), px(WINDOW_HEIGHT)),
+                cx,
+            ))),
+            ..Default::default()
+        };
+        cx.open_window(options, Self::view).unwrap();
+    }
+
+    pub fn view(_window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(Self::new)
+    }
+
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            db_path: cx.global::<Config>().db_path.clone(),
+            focus_handle: cx.focus_handle(),
+            snapshots: vec![],
+            selected: None,
+            errors: ErrorBanner::default(),
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let db_path = self.db_path.clone();
+        let snapshots = cx
+            .background_executor()
+            .spawn(async move { list_snapshots(&db_path) });
+        self.on_reply(
+            async move { snapshots.await.map_err(storage) },
+            cx,
+            |this, snapshots, cx| {
+                this.snapshots = snapshots;
+                this.selected = None;
+                cx.notify();
+            },
+        );
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = (ix < self.snapshots.len()).then_some(ix);
+        cx.notify();
+    }
+
+    /// Replaces the notes with the selected snapshot. The notes it replaces
+    /// are snapshotted first, so they show up in the list to go back to.
+    fn restore(&mut self, cx: &mut Context<Self>) {
+        let Some(snapshot) = self.selected.map(|ix| self.snapshots[ix].path.clone()) else {
+            return;
+        };
+        let db_path = self.db_path.clone();
+        let now = cx.global::<AppHandler>().note_handler().now();
+        let restored = cx.background_executor().spawn(async move {
+            restore_snapshot(&db_path, &snapshot, now.await?).map_err(storage)
+        });
+        self.on_reply(restored, cx, |this, _, cx| {
+            let note_handler = cx.global::<AppHandler>().note_handler();
+            cx.spawn(async move |_, cx| reload(cx, &note_handler).await)
+                .detach();
+            this.reload(cx);
+        });
+    }
+}
+
+fn storage(err: DbError) -> KernelError {
+    KernelError::Storage(Box::new(err))
+}
+
+impl ShowError for SnapshotWindow {
+    fn error_banner(&mut self) -> &mut ErrorBanner {
+        &mut self.errors
+    }
+}
+
+impl Focusable for SnapshotWindow {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for SnapshotWindow {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let snapshots = self
+            .snapshots
+            .iter()
+            .enumerate()
+            .map(|(ix, snapshot)| {
+                let taken_at = snapshot
+                    .taken_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S");
+                let notes = match snapshot.note_count {
+                    Some(1) => "1 note".to_string(),
+                    Some(count) => format!("{count} notes"),
+                    None => "unreadable".to_string(),
+                };
+                div()
+                    .id(("snapshot", ix))
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(hsla(0., 0., 0., 0.1))
+                    .when(self.selected == Some(ix), |row| {
+                        row.bg(hsla(0.15, 0.96, 0.75, 1.))
+                    })
+                    .child(SharedString::from(taken_at.to_string()))
+                    .child(div().text_sm().child(SharedString::from(notes)))
+                    .on_click(cx.listener(move |this, _, _, cx| this.select(ix, cx)))
+            })
+            .collect::<Vec<_>>();
+        let title = if self.snapshots.is_empty() {
+            "No backups yet"
+        } else {
+            "Restore the notes as they were at"
+        };
+
+        div()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(white())
+            .text_color(black())
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .justify_between()
+                    .p_2()
+                    .child(div().font_weight(FontWeight::BOLD).child(title))
+                    .child(
+                        Button::new("restore")
+                            .label("Restore")
+                            .small()
+                            .primary()
+                            .disabled(self.selected.is_none())
+                            .on_click(cx.listener(|this, _, _, cx| this.restore(cx))),
+                    ),
+            )
+            .children(
+                self.errors
+                    .render(cx.listener(|this, _, _, cx| this.dismiss_error(cx))),
+            )
+            .child(
+                div()
+                    .id("snapshots")
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(snapshots),
+            )
+    }
+}
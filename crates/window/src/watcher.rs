@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use gpui::{App, AsyncApp};
+use handler::async_note_handler::AsyncNoteHandler;
+use kernel::model::note::NoteSortKey;
+use registry::{global_model::app_handler::AppHandler, set_notes};
+
+use crate::{editor_delegate::EditorDelegate, notification::notify_error};
+
+/// How often the database is checked for changes made by another process.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches for another sticky process changing the notes, and brings the
+/// windows of this one up to date when it does.
+pub fn init(cx: &mut App) {
+    cx.spawn(async move |cx| {
+        let mut seen = None;
+        // A failing database fails every poll; tell the user once.
+        let mut failure = None;
+        loop {
+            cx.background_executor().timer(POLL_INTERVAL).await;
+            // Read the handler every time, since recovery may replace it.
+            let Ok(note_handler) = cx.update(|cx| cx.global::<AppHandler>().note_handler()) else {
+                return;
+            };
+            let version = match note_handler.data_version().await {
+                Ok(version) => version,
+                Err(err) => {
+                    let message = err.to_string();
+                    if failure.as_ref() != Some(&message) {
+                        cx.update(|cx| notify_error(cx, &err)).ok();
+                        failure = Some(message);
+                    }
+                    continue;
+                }
+            };
+            failure = None;
+            if seen.replace(version).is_none_or(|seen| seen == version) {
+                continue;
+            }
+            reload(cx, &note_handler).await;
+        }
+    })
+    .detach();
+}
+
+/// Brings the windows up to date with the notes in the database, after they
+/// were changed behind their backs.
+pub(crate) async fn reload(cx: &mut AsyncApp, note_handler: &AsyncNoteHandler) {
+    match note_handler.get_all(NoteSortKey::default()).await {
+        Ok(notes) => {
+            cx.update(|cx| {
+                EditorDelegate::sync_notes(cx, &notes);
+                set_notes(cx, notes);
+            })
+            .ok();
+        }
+        Err(err) => {
+            cx.update(|cx| notify_error(cx, &err)).ok();
+        }
+    }
+}